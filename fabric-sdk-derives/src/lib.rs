@@ -1,6 +1,6 @@
 use proc_macro:: TokenStream;
 use quote::quote;
-use syn::{Ident, ItemFn, LitStr, Path, Token, parse::{Parse, ParseStream}, punctuated::Punctuated, spanned::Spanned};
+use syn::{Ident, ItemFn, LitStr, Path, ReturnType, Token, Type, parse::{Parse, ParseStream}, punctuated::Punctuated, spanned::Spanned};
 
 struct RouteInput {
     paths: Punctuated<Path, Token![,]>,
@@ -84,21 +84,36 @@ pub fn transaction(args: TokenStream, item: TokenStream) -> TokenStream {
             match ::fabric_sdk::prelude::serde_json::from_str(args[#indices].as_str()){
                 Ok(value) => value,
                 Err(_) => {
-                    ::fabric_sdk::prelude::serde_json::from_str(format!("\"{}\"",args[#indices]).as_str()).map_err(|e| format!("Unable to deserialize argument; {e}"))?
+                    ::fabric_sdk::prelude::serde_json::from_str(format!("\"{}\"",args[#indices]).as_str()).map_err(|e| ::fabric_sdk::error::ChaincodeError::bad_request(format!("Unable to deserialize argument; {e}")))?
                 }
             }
         ),*
     };
 
+    // Functions returning a `Result` report `Err` as an error response, with the
+    // status of a `ChaincodeError` or 500 for any other error type.
+    let serialized = if returns_result(&item.sig.output) {
+        quote! {
+            match #fn_ident(ctx, #indexed_args).await {
+                Ok(value) => ::fabric_sdk::prelude::serde_json::to_string(&value).map_err(::fabric_sdk::error::ChaincodeError::from_error),
+                Err(err) => Err(::fabric_sdk::error::ChaincodeError::from_error(err)),
+            }
+        }
+    } else {
+        quote! {
+            ::fabric_sdk::prelude::serde_json::to_string(&#fn_ident(ctx, #indexed_args).await).map_err(::fabric_sdk::error::ChaincodeError::from_error)
+        }
+    };
+
     let generated = quote! {
             pub struct #name_ident{}
             impl ::fabric_sdk::chaincode::Callable for #name_ident {
-                fn call(&self, ctx: ::fabric_sdk::chaincode::context::Context, args: Vec<String>) -> ::fabric_sdk::prelude::tokio::task::JoinHandle<Result<String, String>> {
+                fn call(&self, ctx: ::fabric_sdk::chaincode::context::Context, args: Vec<String>) -> ::fabric_sdk::prelude::tokio::task::JoinHandle<Result<String, ::fabric_sdk::error::ChaincodeError>> {
                     ::fabric_sdk::prelude::tokio::spawn(async move{
                         if args.len() != #argument_size{
-                            return Err(format!("Found {} arguments but expected {}",args.len(),#argument_size));
+                            return Err(::fabric_sdk::error::ChaincodeError::bad_request(format!("Found {} arguments but expected {}",args.len(),#argument_size)));
                         }
-                        ::fabric_sdk::prelude::serde_json::to_string(&#fn_ident(ctx, #indexed_args).await).map_err(|e| e.to_string())
+                        #serialized
                     })
                 }
                 fn name(&self) -> &str{
@@ -111,6 +126,23 @@ pub fn transaction(args: TokenStream, item: TokenStream) -> TokenStream {
     generated.into()
 }
 
+/// Whether the function is declared to return a `Result`. This is decided on
+/// the written type, so aliases of `Result` under another name are serialized
+/// like any other value.
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
 fn adapt_name(name_string: &String) -> String{
     let mut name = String::with_capacity(name_string.len());
     let mut capitalize_next = true;
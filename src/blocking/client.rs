@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+
+use crate::{
+    blocking::{LifecycleClient, new_runtime},
+    error::SubmitError,
+    fabric::{
+        common::Envelope,
+        discovery::QueryResult,
+        gateway::{ChaincodeEventsResponse, CommitStatusResponse, SignedChaincodeEventsRequest},
+        protos::{ProposalResponse, SignedProposal},
+    },
+    gateway::{
+        chaincode::{ChaincodeCallBuilder, PreparedTransaction},
+        client::ChaincodeEventsRequestBuilder,
+        discovery::{DiscoveryCallBuilder, PreparedDiscoveryCall},
+    },
+};
+
+/// Blocking counterpart of [crate::gateway::client::Client].
+///
+/// Endorsing, submitting and waiting for the commit of a transaction are
+/// methods of the client here instead of [SignedProposal] and [Envelope].
+pub struct Client {
+    pub(crate) inner: crate::gateway::client::Client,
+    pub(crate) runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Wraps an async client, which does not need to be connected yet.
+    pub fn new(inner: crate::gateway::client::Client) -> Self {
+        Self {
+            inner,
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Returns the wrapped async client.
+    pub fn get_async_client(&self) -> &crate::gateway::client::Client {
+        &self.inner
+    }
+
+    pub fn connect(&mut self) -> Result<(), tonic::transport::Error> {
+        self.runtime.block_on(self.inner.connect())
+    }
+
+    /// See [crate::gateway::client::Client::get_chaincode_call_builder].
+    pub fn get_chaincode_call_builder(&self) -> ChaincodeCallBuilder {
+        self.inner.get_chaincode_call_builder()
+    }
+
+    pub fn get_discovery_call_builder(&self) -> DiscoveryCallBuilder {
+        self.inner.get_discovery_call_builder()
+    }
+
+    /// See [crate::gateway::client::Client::submit_discover_call].
+    pub fn submit_discover_call(
+        &self,
+        prepared_discovery_call: PreparedDiscoveryCall,
+    ) -> Result<Vec<QueryResult>, SubmitError> {
+        self.runtime
+            .block_on(self.inner.submit_discover_call(prepared_discovery_call))
+    }
+
+    /// Sends the proposal to the peers for endorsement and returns the
+    /// endorsed transaction. See [SignedProposal::endorse].
+    pub fn endorse(&self, signed_proposal: SignedProposal) -> Result<Envelope, SubmitError> {
+        self.runtime.block_on(signed_proposal.endorse(&self.inner))
+    }
+
+    /// See [SignedProposal::endorse_with_organizations].
+    pub fn endorse_with_organizations(
+        &self,
+        signed_proposal: SignedProposal,
+        endorsing_organizations: Vec<String>,
+    ) -> Result<Envelope, SubmitError> {
+        self.runtime.block_on(
+            signed_proposal.endorse_with_organizations(&self.inner, endorsing_organizations),
+        )
+    }
+
+    /// See [PreparedTransaction::endorse].
+    pub fn endorse_prepared(
+        &self,
+        prepared_transaction: PreparedTransaction,
+    ) -> Result<Envelope, SubmitError> {
+        self.runtime
+            .block_on(prepared_transaction.endorse(&self.inner))
+    }
+
+    /// Submits an endorsed transaction to the orderer. See [Envelope::submit].
+    pub fn submit(&self, envelope: &mut Envelope) -> Result<(), SubmitError> {
+        self.runtime.block_on(envelope.submit(&self.inner))?;
+        Ok(())
+    }
+
+    /// Blocks until the submitted transaction is committed. See
+    /// [Envelope::wait_for_commit].
+    pub fn wait_for_commit(
+        &self,
+        envelope: &Envelope,
+    ) -> Result<CommitStatusResponse, SubmitError> {
+        self.runtime.block_on(envelope.wait_for_commit(&self.inner))
+    }
+
+    /// See [crate::gateway::client::Client::commit_status].
+    pub fn commit_status(
+        &self,
+        transaction_id: String,
+        channel_id: String,
+    ) -> Result<CommitStatusResponse, SubmitError> {
+        self.runtime
+            .block_on(self.inner.commit_status(transaction_id, channel_id))
+    }
+
+    /// See [crate::gateway::client::Client::evaluate].
+    pub fn evaluate(
+        &self,
+        signed_proposal: SignedProposal,
+        transaction_id: String,
+        channel_id: String,
+    ) -> Result<Vec<u8>, SubmitError> {
+        self.runtime.block_on(
+            self.inner
+                .evaluate(signed_proposal, transaction_id, channel_id),
+        )
+    }
+
+    /// See [crate::gateway::client::Client::evaluate_with_organizations].
+    pub fn evaluate_with_organizations(
+        &self,
+        signed_proposal: SignedProposal,
+        transaction_id: String,
+        channel_id: String,
+        target_organizations: Vec<String>,
+    ) -> Result<Vec<u8>, SubmitError> {
+        self.runtime
+            .block_on(self.inner.evaluate_with_organizations(
+                signed_proposal,
+                transaction_id,
+                channel_id,
+                target_organizations,
+            ))
+    }
+
+    /// See [PreparedTransaction::evaluate].
+    pub fn evaluate_prepared(
+        &self,
+        prepared_transaction: PreparedTransaction,
+    ) -> Result<Vec<u8>, SubmitError> {
+        self.runtime
+            .block_on(prepared_transaction.evaluate(&self.inner))
+    }
+
+    pub fn get_chaincode_events_request_builder(&self) -> ChaincodeEventsRequestBuilder {
+        self.inner.get_chaincode_events_request_builder()
+    }
+
+    /// Opens a chaincode event stream. The returned iterator blocks until the
+    /// next block with matching events arrives and ends when the peer closes
+    /// the stream. See [crate::gateway::client::Client::chaincode_events].
+    pub fn chaincode_events(
+        &self,
+        request: SignedChaincodeEventsRequest,
+    ) -> Result<ChaincodeEvents, SubmitError> {
+        let stream = self
+            .runtime
+            .block_on(self.inner.chaincode_events(request))?;
+        Ok(ChaincodeEvents {
+            stream,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Creates a blocking [LifecycleClient] on this peer connection.
+    pub fn get_lifecycle_client(&self) -> LifecycleClient<'_> {
+        LifecycleClient::new(self)
+    }
+
+    /// See [crate::gateway::client::Client::process_proposal].
+    pub fn process_proposal(
+        &self,
+        signed_proposal: SignedProposal,
+    ) -> Result<ProposalResponse, SubmitError> {
+        self.runtime
+            .block_on(self.inner.process_proposal(signed_proposal))
+    }
+}
+
+/// Iterator over the responses of a chaincode event stream, one per block
+/// containing requested events.
+pub struct ChaincodeEvents {
+    stream: tonic::Streaming<ChaincodeEventsResponse>,
+    runtime: Arc<Runtime>,
+}
+
+impl Iterator for ChaincodeEvents {
+    type Item = Result<ChaincodeEventsResponse, SubmitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime
+            .block_on(self.stream.message())
+            .map_err(|err| {
+                SubmitError::NodeError(crate::implement::grpc_error::format_grpc_error(&err))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::LifecycleError,
+        gateway::client::ClientBuilder,
+        identity::{Identity, IdentityBuilder},
+    };
+
+    fn test_identity() -> Identity {
+        IdentityBuilder::from_pem(include_str!("../../tests/resources/pki/user1-cert.pem"))
+            .unwrap()
+            .with_msp("Org1MSP")
+            .unwrap()
+            .with_private_key(include_str!("../../tests/resources/pki/user1-key.pem"))
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn test_client() -> Client {
+        let client = ClientBuilder::new()
+            .with_identity(test_identity())
+            .unwrap()
+            .with_tls(include_str!("../../tests/resources/pki/ca-cert.pem"))
+            .unwrap()
+            .build()
+            .unwrap();
+        Client::new(client)
+    }
+
+    #[test]
+    fn runs_requests_without_a_runtime() {
+        let client = test_client();
+        let proposal = client
+            .get_chaincode_call_builder()
+            .with_channel_name("mychannel")
+            .unwrap()
+            .with_chaincode_id("basic")
+            .unwrap()
+            .with_function_name("GetAllAssets")
+            .unwrap()
+            .build_prepared()
+            .unwrap();
+        assert!(matches!(
+            client.evaluate_prepared(proposal),
+            Err(SubmitError::NotConnected)
+        ));
+        assert!(matches!(
+            client.get_lifecycle_client().query_installed_chaincodes(),
+            Err(LifecycleError::SubmitError(SubmitError::NotConnected))
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::runtime::Runtime;
+
+use crate::{
+    blocking::new_runtime,
+    error::FabricCAError,
+    fabric_ca::{
+        AddAffiliationRequest, Affiliation, CAIdentity, CAInfo, CertificateRevocationList,
+        CertificatesRequest, EnrollOptions, Enrollment, GenCrlRequest, IssuedCertificate,
+        ModifyAffiliationRequest, ModifyIdentityRequest, ReenrollOptions, RegisterIdentityRequest,
+        RevokeRequest,
+    },
+    identity::Identity,
+};
+
+/// Blocking counterpart of [crate::fabric_ca::FabricCAClient].
+pub struct FabricCAClient {
+    inner: crate::fabric_ca::FabricCAClient,
+    runtime: Arc<Runtime>,
+}
+
+impl FabricCAClient {
+    /// Wraps an async client built with [crate::fabric_ca::FabricCAClientBuilder].
+    pub fn new(inner: crate::fabric_ca::FabricCAClient) -> Self {
+        Self {
+            inner,
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Returns the wrapped async client.
+    pub fn get_async_client(&self) -> &crate::fabric_ca::FabricCAClient {
+        &self.inner
+    }
+
+    pub fn get_ca_info(&self) -> Result<CAInfo, FabricCAError> {
+        self.runtime.block_on(self.inner.get_ca_info())
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::enroll].
+    pub fn enroll(
+        &self,
+        enrollment_id: impl AsRef<str>,
+        secret: impl AsRef<str>,
+        options: EnrollOptions,
+    ) -> Result<Enrollment, FabricCAError> {
+        self.runtime
+            .block_on(self.inner.enroll(enrollment_id, secret, options))
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::reenroll].
+    pub fn reenroll(
+        &self,
+        identity: &Identity,
+        options: ReenrollOptions,
+    ) -> Result<Enrollment, FabricCAError> {
+        self.runtime
+            .block_on(self.inner.reenroll(identity, options))
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::reenroll_if_expiring].
+    pub fn reenroll_if_expiring(
+        &self,
+        identity: &Identity,
+        threshold: Duration,
+        options: ReenrollOptions,
+    ) -> Result<Option<Enrollment>, FabricCAError> {
+        self.runtime.block_on(
+            self.inner
+                .reenroll_if_expiring(identity, threshold, options),
+        )
+    }
+
+    pub fn list_identities(&self) -> Result<Vec<CAIdentity>, FabricCAError> {
+        self.runtime.block_on(self.inner.list_identities())
+    }
+
+    pub fn get_identity(&self, id: impl AsRef<str>) -> Result<CAIdentity, FabricCAError> {
+        self.runtime.block_on(self.inner.get_identity(id))
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::register_identity].
+    pub fn register_identity(&self, req: RegisterIdentityRequest) -> Result<String, FabricCAError> {
+        self.runtime.block_on(self.inner.register_identity(req))
+    }
+
+    pub fn modify_identity(
+        &self,
+        id: impl AsRef<str>,
+        req: ModifyIdentityRequest,
+    ) -> Result<CAIdentity, FabricCAError> {
+        self.runtime.block_on(self.inner.modify_identity(id, req))
+    }
+
+    pub fn remove_identity(&self, id: impl AsRef<str>) -> Result<(), FabricCAError> {
+        self.runtime.block_on(self.inner.remove_identity(id))
+    }
+
+    pub fn list_affiliations(&self) -> Result<Affiliation, FabricCAError> {
+        self.runtime.block_on(self.inner.list_affiliations())
+    }
+
+    pub fn get_affiliation(&self, name: impl AsRef<str>) -> Result<Affiliation, FabricCAError> {
+        self.runtime.block_on(self.inner.get_affiliation(name))
+    }
+
+    pub fn add_affiliation(
+        &self,
+        req: AddAffiliationRequest,
+    ) -> Result<Affiliation, FabricCAError> {
+        self.runtime.block_on(self.inner.add_affiliation(req))
+    }
+
+    pub fn modify_affiliation(
+        &self,
+        name: impl AsRef<str>,
+        req: ModifyAffiliationRequest,
+    ) -> Result<Affiliation, FabricCAError> {
+        self.runtime
+            .block_on(self.inner.modify_affiliation(name, req))
+    }
+
+    pub fn remove_affiliation(
+        &self,
+        name: impl AsRef<str>,
+        force: bool,
+    ) -> Result<Affiliation, FabricCAError> {
+        self.runtime
+            .block_on(self.inner.remove_affiliation(name, force))
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::list_certificates].
+    pub fn list_certificates(
+        &self,
+        req: CertificatesRequest,
+    ) -> Result<Vec<IssuedCertificate>, FabricCAError> {
+        self.runtime.block_on(self.inner.list_certificates(req))
+    }
+
+    /// See [crate::fabric_ca::FabricCAClient::generate_crl].
+    pub fn generate_crl(
+        &self,
+        req: GenCrlRequest,
+    ) -> Result<CertificateRevocationList, FabricCAError> {
+        self.runtime.block_on(self.inner.generate_crl(req))
+    }
+
+    pub fn revoke(&self, req: RevokeRequest) -> Result<(), FabricCAError> {
+        self.runtime.block_on(self.inner.revoke(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fabric_ca::FabricCAClientBuilder;
+
+    #[test]
+    fn reports_request_errors() {
+        // Nothing listens here, so the request fails once it is sent.
+        let ca = FabricCAClient::new(
+            FabricCAClientBuilder::new()
+                .with_url("http://127.0.0.1:1")
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+        assert!(matches!(ca.get_ca_info(), Err(FabricCAError::HttpError(_))));
+        assert!(matches!(
+            ca.list_identities(),
+            Err(FabricCAError::NotConfigured)
+        ));
+    }
+}
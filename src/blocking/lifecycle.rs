@@ -0,0 +1,150 @@
+use crate::{
+    blocking::Client,
+    error::LifecycleError,
+    fabric::{
+        gateway::CommitStatusResponse,
+        lifecycle::{
+            ApproveChaincodeDefinitionForMyOrgArgs, CheckCommitReadinessArgs,
+            CheckCommitReadinessResult, CommitChaincodeDefinitionArgs, InstallChaincodeResult,
+            QueryApprovedChaincodeDefinitionResult, QueryApprovedChaincodeDefinitionsResult,
+            QueryChaincodeDefinitionResult, QueryChaincodeDefinitionsResult,
+            QueryInstalledChaincodeResult, QueryInstalledChaincodesResult,
+        },
+    },
+};
+
+/// Blocking counterpart of [crate::gateway::lifecycle::LifecycleClient],
+/// created with [Client::get_lifecycle_client].
+pub struct LifecycleClient<'a> {
+    client: &'a Client,
+    inner: crate::gateway::lifecycle::LifecycleClient<'a>,
+}
+
+impl<'a> LifecycleClient<'a> {
+    pub fn new(client: &'a Client) -> Self {
+        Self {
+            client,
+            inner: crate::gateway::lifecycle::LifecycleClient::new(&client.inner),
+        }
+    }
+
+    /// See [crate::gateway::lifecycle::LifecycleClient::install_chaincode].
+    pub fn install_chaincode(
+        &self,
+        package: Vec<u8>,
+    ) -> Result<InstallChaincodeResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.install_chaincode(package))
+    }
+
+    pub fn query_installed_chaincodes(
+        &self,
+    ) -> Result<QueryInstalledChaincodesResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.query_installed_chaincodes())
+    }
+
+    pub fn query_installed_chaincode(
+        &self,
+        package_id: impl Into<String>,
+    ) -> Result<QueryInstalledChaincodeResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.query_installed_chaincode(package_id))
+    }
+
+    pub fn get_installed_chaincode_package(
+        &self,
+        package_id: impl Into<String>,
+    ) -> Result<Vec<u8>, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.get_installed_chaincode_package(package_id))
+    }
+
+    /// See [crate::gateway::lifecycle::LifecycleClient::approve_chaincode_definition].
+    pub fn approve_chaincode_definition(
+        &self,
+        channel_name: impl Into<String>,
+        args: ApproveChaincodeDefinitionForMyOrgArgs,
+    ) -> Result<CommitStatusResponse, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.approve_chaincode_definition(channel_name, args))
+    }
+
+    pub fn check_commit_readiness(
+        &self,
+        channel_name: impl Into<String>,
+        args: CheckCommitReadinessArgs,
+    ) -> Result<CheckCommitReadinessResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.check_commit_readiness(channel_name, args))
+    }
+
+    /// See [crate::gateway::lifecycle::LifecycleClient::commit_chaincode_definition].
+    pub fn commit_chaincode_definition(
+        &self,
+        channel_name: impl Into<String>,
+        args: CommitChaincodeDefinitionArgs,
+        endorsing_peers: &[&Client],
+    ) -> Result<CommitStatusResponse, LifecycleError> {
+        let endorsing_peers = endorsing_peers
+            .iter()
+            .map(|client| &client.inner)
+            .collect::<Vec<_>>();
+        self.client
+            .runtime
+            .block_on(
+                self.inner
+                    .commit_chaincode_definition(channel_name, args, &endorsing_peers),
+            )
+    }
+
+    pub fn query_chaincode_definition(
+        &self,
+        channel_name: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<QueryChaincodeDefinitionResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.query_chaincode_definition(channel_name, name))
+    }
+
+    pub fn query_chaincode_definitions(
+        &self,
+        channel_name: impl Into<String>,
+    ) -> Result<QueryChaincodeDefinitionsResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(self.inner.query_chaincode_definitions(channel_name))
+    }
+
+    /// See [crate::gateway::lifecycle::LifecycleClient::query_approved_chaincode_definition].
+    pub fn query_approved_chaincode_definition(
+        &self,
+        channel_name: impl Into<String>,
+        name: impl Into<String>,
+        sequence: i64,
+    ) -> Result<QueryApprovedChaincodeDefinitionResult, LifecycleError> {
+        self.client
+            .runtime
+            .block_on(
+                self.inner
+                    .query_approved_chaincode_definition(channel_name, name, sequence),
+            )
+    }
+
+    pub fn query_approved_chaincode_definitions(
+        &self,
+        channel_name: impl Into<String>,
+    ) -> Result<QueryApprovedChaincodeDefinitionsResult, LifecycleError> {
+        self.client.runtime.block_on(
+            self.inner
+                .query_approved_chaincode_definitions(channel_name),
+        )
+    }
+}
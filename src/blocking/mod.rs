@@ -0,0 +1,48 @@
+//! A blocking API for synchronous programs, similar to `reqwest::blocking`.
+//!
+//! The types in this module wrap their async counterparts and drive them on a
+//! runtime they own, so no tokio runtime has to be set up by the caller.
+//! Event streams are exposed as iterators.
+//!
+//! They must not be used from within an async runtime: blocking on the
+//! internal runtime from inside another one panics.
+//!
+//! ```rust
+//! let client = ClientBuilder::new()
+//!     .with_identity(identity)?
+//!     .with_tls(tls_cert)?
+//!     .with_scheme("https")?
+//!     .with_authority("localhost:7051")?
+//!     .build()?;
+//! let mut client = fabric_sdk::blocking::Client::new(client);
+//! client.connect()?;
+//! let proposal = client
+//!     .get_chaincode_call_builder()
+//!     .with_channel_name("mychannel")?
+//!     .with_chaincode_id("basic")?
+//!     .with_function_name("GetAllAssets")?
+//!     .build_prepared()?;
+//! let assets = client.evaluate_prepared(proposal)?;
+//! ```
+
+mod client;
+mod fabric_ca;
+mod lifecycle;
+
+pub use client::{ChaincodeEvents, Client};
+pub use fabric_ca::FabricCAClient;
+pub use lifecycle::LifecycleClient;
+
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+
+/// Creates the runtime a blocking client drives its requests on.
+fn new_runtime() -> Arc<Runtime> {
+    Arc::new(
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Unable to start tokio runtime"),
+    )
+}
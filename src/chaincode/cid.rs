@@ -0,0 +1,244 @@
+//! Access control helpers on the identity that submitted a transaction, the
+//! counterpart of the `cid` package of the Go chaincode library.
+
+use prost::Message;
+use x509_parser::{
+    oid_registry::{
+        OID_X509_COMMON_NAME, OID_X509_COUNTRY_NAME, OID_X509_LOCALITY_NAME,
+        OID_X509_ORGANIZATION_NAME, OID_X509_ORGANIZATIONAL_UNIT, OID_X509_POSTAL_CODE,
+        OID_X509_SERIALNUMBER, OID_X509_STATE_OR_PROVINCE_NAME, OID_X509_STREET_ADDRESS, Oid,
+    },
+    x509::X509Name,
+};
+
+use crate::{
+    error::{ChaincodeError, ChaincodeStubError},
+    fabric::{common::msp_role::MspRoleType, msp::SerializedIdentity},
+    identity::CertificateInfo,
+    implement::certificate::{certificate_der, with_certificate},
+    policy::NodeOuConfig,
+};
+
+/// The identity that submitted the transaction, obtained with
+/// [Context::get_client_identity](crate::chaincode::context::Context::get_client_identity).
+///
+/// ```no_run
+/// # use fabric_sdk::prelude::*;
+/// # async fn example(ctx: Context) -> Result<(), fabric_sdk::error::ChaincodeError> {
+/// let client = ctx.get_client_identity()?;
+/// if client.msp_id() != "Org1MSP" {
+///     return Err(fabric_sdk::error::ChaincodeError::forbidden("Only Org1 may do this"));
+/// }
+/// client.assert_attribute_value("role", "auditor")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientIdentity {
+    msp_id: String,
+    id: String,
+    certificate: CertificateInfo,
+    certificate_pem: Vec<u8>,
+}
+
+impl ClientIdentity {
+    /// Parses the serialized identity of a transaction creator, as returned by
+    /// [Context::get_creator](crate::chaincode::context::Context::get_creator).
+    pub fn new(creator: &[u8]) -> Result<Self, ChaincodeStubError> {
+        let identity = SerializedIdentity::decode(creator).map_err(|err| {
+            ChaincodeStubError::DecodeError(format!("Invalid serialized identity: {}", err))
+        })?;
+        let invalid_certificate =
+            || ChaincodeStubError::DecodeError("Invalid creator certificate".to_string());
+        let der = certificate_der(&identity.id_bytes).ok_or_else(invalid_certificate)?;
+        let id = with_certificate(&der, |cert| {
+            format!(
+                "x509::{}::{}",
+                distinguished_name(cert.subject()),
+                distinguished_name(cert.issuer())
+            )
+        })
+        .ok_or_else(invalid_certificate)?;
+        let certificate = CertificateInfo::parse(&der)
+            .map_err(|err| ChaincodeStubError::DecodeError(err.to_string()))?;
+        Ok(ClientIdentity {
+            msp_id: identity.mspid,
+            id,
+            certificate,
+            certificate_pem: identity.id_bytes,
+        })
+    }
+
+    /// Returns the ID of the MSP the identity belongs to, e.g. `Org1MSP`.
+    pub fn msp_id(&self) -> &str {
+        &self.msp_id
+    }
+
+    /// Returns an ID that is unique within the MSP, in the form
+    /// `x509::<subject>::<issuer>` with the distinguished names formatted like
+    /// Go, e.g. `x509::CN=user1,OU=client+OU=org1,O=Hyperledger::CN=ca.org1.example.com,...`.
+    ///
+    /// The Go library returns this string base64 encoded.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the parsed certificate of the identity.
+    pub fn certificate(&self) -> &CertificateInfo {
+        &self.certificate
+    }
+
+    /// Returns the certificate as it was serialized by the client, usually PEM.
+    pub fn certificate_pem(&self) -> &[u8] {
+        &self.certificate_pem
+    }
+
+    /// Returns an attribute the Fabric CA put into the certificate, e.g.
+    /// `hf.EnrollmentID` or a custom attribute requested during enrollment.
+    pub fn attribute_value(&self, name: &str) -> Option<&str> {
+        self.certificate.attribute(name)
+    }
+
+    /// Fails with a `403` if the attribute is missing or has another value, so
+    /// it can guard a transaction function with `?`.
+    pub fn assert_attribute_value(&self, name: &str, value: &str) -> Result<(), ChaincodeError> {
+        match self.attribute_value(name) {
+            Some(actual) if actual == value => Ok(()),
+            Some(actual) => Err(ChaincodeError::forbidden(format!(
+                "Attribute '{}' equals '{}' instead of '{}'",
+                name, actual, value
+            ))),
+            None => Err(ChaincodeError::forbidden(format!(
+                "Attribute '{}' was not found",
+                name
+            ))),
+        }
+    }
+
+    /// Returns the NodeOU role of the identity, e.g. [MspRoleType::Client].
+    /// [NodeOuConfig::default] has the OUs the Fabric CA and `cryptogen` use.
+    pub fn role(&self, node_ous: &NodeOuConfig) -> Option<MspRoleType> {
+        self.certificate.node_ou_role(node_ous)
+    }
+}
+
+/// The attributes of Go's `pkix.Name` in the order of `ToRDNSequence`, and
+/// whether the name keeps only the last value.
+const NAME_ATTRIBUTES: [(Oid<'static>, &str, bool); 9] = [
+    (OID_X509_COUNTRY_NAME, "C", false),
+    (OID_X509_STATE_OR_PROVINCE_NAME, "ST", false),
+    (OID_X509_LOCALITY_NAME, "L", false),
+    (OID_X509_STREET_ADDRESS, "STREET", false),
+    (OID_X509_POSTAL_CODE, "POSTALCODE", false),
+    (OID_X509_ORGANIZATION_NAME, "O", false),
+    (OID_X509_ORGANIZATIONAL_UNIT, "OU", false),
+    (OID_X509_COMMON_NAME, "CN", true),
+    (OID_X509_SERIALNUMBER, "SERIALNUMBER", true),
+];
+
+/// Formats a distinguished name like Go's `getDN`, which prints
+/// `pkix.Name.ToRDNSequence()`: the values of each attribute in one RDN
+/// joined by `+`, most specific RDN first, separated by commas without
+/// spaces. Other attributes are dropped, as `pkix.Name` doesn't keep them.
+fn distinguished_name(name: &X509Name) -> String {
+    let mut rdns = Vec::new();
+    for (oid, label, single) in &NAME_ATTRIBUTES {
+        let values: Vec<&str> = name
+            .iter_by_oid(oid)
+            .filter_map(|attribute| attribute.as_str().ok())
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+        let values = if *single {
+            &values[values.len() - 1..]
+        } else {
+            &values[..]
+        };
+        rdns.push(
+            values
+                .iter()
+                .map(|value| format!("{}={}", label, escape(value)))
+                .collect::<Vec<_>>()
+                .join("+"),
+        );
+    }
+    rdns.reverse();
+    rdns.join(",")
+}
+
+fn escape(value: &str) -> String {
+    let last = value.chars().count().saturating_sub(1);
+    let mut escaped = String::with_capacity(value.len());
+    for (index, c) in value.chars().enumerate() {
+        let special = matches!(c, ',' | '+' | '"' | '\\' | '<' | '>' | ';')
+            || (index == 0 && matches!(c, ' ' | '#'))
+            || (index == last && c == ' ');
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(mspid: &str, cert: &str) -> Vec<u8> {
+        SerializedIdentity {
+            mspid: mspid.to_string(),
+            id_bytes: cert.as_bytes().to_vec(),
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn reads_the_submitting_identity() {
+        let cert = include_str!("../../tests/resources/pki/user1-cert.pem");
+        let client = ClientIdentity::new(&creator("Org1MSP", cert)).unwrap();
+        assert_eq!(client.msp_id(), "Org1MSP");
+        assert_eq!(
+            client.id(),
+            "x509::CN=user1,OU=client+OU=org1+OU=department1,O=Hyperledger,ST=North Carolina,C=US\
+             ::CN=ca.org1.example.com,O=org1.example.com,L=Durham,ST=North Carolina,C=US"
+        );
+        assert_eq!(client.certificate().common_name.as_deref(), Some("user1"));
+        assert_eq!(client.certificate_pem(), cert.as_bytes());
+        assert_eq!(client.attribute_value("role"), Some("auditor"));
+        assert_eq!(client.attribute_value("missing"), None);
+        assert_eq!(
+            client.role(&NodeOuConfig::default()),
+            Some(MspRoleType::Client)
+        );
+
+        assert!(client.assert_attribute_value("role", "auditor").is_ok());
+        let mismatch = client.assert_attribute_value("role", "admin").unwrap_err();
+        assert_eq!(mismatch.status, 403);
+        assert_eq!(
+            mismatch.message,
+            "Attribute 'role' equals 'auditor' instead of 'admin'"
+        );
+        assert_eq!(
+            client
+                .assert_attribute_value("missing", "value")
+                .unwrap_err()
+                .message,
+            "Attribute 'missing' was not found"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_creators() {
+        assert!(matches!(
+            ClientIdentity::new(&[0xff]),
+            Err(ChaincodeStubError::DecodeError(_))
+        ));
+        assert!(matches!(
+            ClientIdentity::new(&creator("Org1MSP", "no certificate")),
+            Err(ChaincodeStubError::DecodeError(_))
+        ));
+        assert_eq!(escape(" a,b+c "), "\\ a\\,b\\+c\\ ");
+    }
+}
@@ -1,182 +1,475 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use crate::{
-    chaincode::{Callable, Metadata, context::Context, message::MessageBuilder, router::Router},
+    chaincode::{
+        Contracts,
+        context::Context,
+        message::MessageBuilder,
+        router::{PendingTransactions, Router},
+    },
+    error::ChaincodeError,
     fabric::{
         common::Status,
         protos::{ChaincodeId, ChaincodeInput, ChaincodeMessage, Response, chaincode_message},
     },
+    telemetry,
 };
-use futures_channel::mpsc::Receiver;
+use futures_channel::mpsc::{Receiver, Sender};
 use futures_util::StreamExt;
 use prost::Message;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
 
-///Handles messages incoming from the peer. Also registers itself to the peer
+///Handles the transactions of one connection to the peer. Also registers the chaincode to the peer
 pub struct MessageHandler {
-    ///Contract registered by this chaincode
-    contracts: HashMap<String, HashMap<String, Box<dyn Callable>>>,
+    ///Contracts registered by this chaincode
+    contracts: Arc<Contracts>,
     ///Helper struct to generate messages to the peer
     message_builder: Arc<Mutex<MessageBuilder>>,
     chaincode_id: ChaincodeId,
     transaction_queue: Receiver<ChaincodeMessage>,
-    peer_response_queue: Arc<Mutex<Receiver<ChaincodeMessage>>>,
+    pending_transactions: PendingTransactions,
+    ///Bounds the number of transactions executing at the same time
+    concurrency_limit: Arc<Semaphore>,
 }
 impl MessageHandler {
+    /// Creates the handler and the [Router] feeding it for a stream to the
+    /// peer, and sends `REGISTER` into `outbound`, the messages to the peer.
     pub async fn new(
-        metadata: &Metadata,
         chaincode_id: ChaincodeId,
-        contracts: HashMap<String, HashMap<String, Box<dyn Callable>>>,
-    ) -> MessageHandler {
-        let (tx, rx) = futures_channel::mpsc::channel::<ChaincodeMessage>(100);
-
-        let (transaction_queue_sender, transaction_queue_receiver) =
-            futures_channel::mpsc::channel(100);
-        let (peer_response_sender, peer_response_receiver) = futures_channel::mpsc::channel(100);
-
-        let router =
-            Router::new(metadata, transaction_queue_sender, peer_response_sender, rx).await;
-        tokio::spawn(async move {
-            eprintln!("[Router] Starting router");
-            router.run().await;
-            eprintln!("[Router] Router stopped");
-        });
-
-        let mut message_builder = MessageBuilder::new(metadata, tx);
-        eprintln!("Current state is CREATED");
+        contracts: Arc<Contracts>,
+        max_concurrency: usize,
+        outbound: Sender<ChaincodeMessage>,
+    ) -> (MessageHandler, Router) {
+        let mut message_builder = MessageBuilder::new(outbound);
+        tracing::info!("Current state is CREATED");
         //Register this chaincode to the peer. This needs to be the very first message
-        message_builder
+        if let Err(err) = message_builder
             .send(
                 chaincode_message::Type::Register,
-                metadata.chaincode_id.encode_to_vec(),
+                chaincode_id.encode_to_vec(),
             )
-            .await;
+            .await
+        {
+            tracing::error!("Error sending REGISTER: {err}");
+        }
         let message_builder = Arc::new(Mutex::new(message_builder));
 
-        MessageHandler {
+        // Up to `max_concurrency` transactions wait for a running one to
+        // complete, the router rejects any further ones.
+        let (transaction_queue_sender, transaction_queue_receiver) =
+            futures_channel::mpsc::channel(max_concurrency);
+        let pending_transactions = PendingTransactions::default();
+        let router = Router::new(
+            transaction_queue_sender,
+            pending_transactions.clone(),
+            message_builder.clone(),
+        );
+
+        let handler = MessageHandler {
             contracts,
             message_builder,
             chaincode_id,
             transaction_queue: transaction_queue_receiver,
-            peer_response_queue: Arc::new(Mutex::new(peer_response_receiver)),
-        }
+            pending_transactions,
+            concurrency_limit: Arc::new(Semaphore::new(max_concurrency)),
+        };
+        (handler, router)
     }
 
+    /// Starts every transaction as soon as it arrives, unless `max_concurrency`
+    /// transactions are already executing. Then the queue is paused until one
+    /// of them completes.
     pub async fn run(mut self) {
         while let Some(message) = self.transaction_queue.next().await {
-            eprintln!("[MessageHandler] Executing transaction {}", message.txid);
-            match ChaincodeInput::decode(message.payload.as_slice()) {
-                Ok(input) => {
-                    //structname:functionname,arg1,arg2,arg3
-                    //First argument is the contract:function
-                    let arg_iter = input.args.iter();
-                    let arguments = arg_iter
-                        .map(|f| {
-                            String::from_utf8(f.clone())
-                                .expect("[MessageHandler] Invalid UTF-8 encoding")
-                        })
-                        .collect::<Vec<String>>();
-                    let contract_function = arguments
-                        .first()
-                        .expect("[MessageHandler] Expected first argument but found nothing")
-                        .split(":")
-                        .collect::<Vec<&str>>();
-
-                    let contract_name = if contract_function.len() > 1 {
-                        contract_function
-                            .first()
-                            .expect("[MessageHandler] Expected contract_name but found nothing")
-                    } else {
-                        ""
-                    };
-                    let function_name = contract_function
-                        .last()
-                        .expect("[MessageHandler] Expected function_name but found nothing");
-                    let response = match self.contracts.get(contract_name) {
-                        Some(contract) => match contract.get(*function_name) {
-                            Some(function) => {
-                                match function
-                                    .call(
-                                        Context::new(
-                                            self.message_builder.clone(),
-                                            message.clone(),
-                                            self.peer_response_queue.clone(),
-                                        ),
-                                        arguments.iter().skip(1).cloned().collect::<Vec<String>>(),
-                                    )
-                                    .await
-                                {
-                                    Ok(result) => match result {
-                                        Ok(message) => Response {
-                                            status: Status::Success.into(),
-                                            message: String::default(),
-                                            payload: message.as_bytes().to_vec(),
-                                        },
-                                        Err(err) => Response {
-                                            status: Status::InternalServerError.into(),
-                                            message: format!(
-                                                "An error occurred during the exection of the chaincode function: {err}"
-                                            ),
-                                            payload: vec![],
-                                        },
-                                    },
-                                    Err(err) => Response {
-                                        status: Status::InternalServerError.into(),
-                                        message: format!(
-                                            "An error occurred during the exection of the chaincode function: {err}"
-                                        ),
-                                        payload: vec![],
-                                    },
-                                }
-                            }
-                            None => Response {
-                                status: Status::NotFound.into(),
-                                message: format!(
-                                    "Function {function_name} not found in contract {contract_name} from chaincode {}",
-                                    self.chaincode_id.name
-                                ),
-                                payload: vec![],
-                            },
-                        },
-                        None => Response {
-                            status: Status::NotFound.into(),
-                            message: format!(
-                                "Contract {contract_name} not found in chaincode {}",
-                                self.chaincode_id.name
-                            ),
-                            payload: vec![],
-                        },
-                    };
+            let permit = self
+                .concurrency_limit
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("[MessageHandler] Concurrency limit closed");
+            self.dispatch(message, permit).await;
+        }
+        tracing::info!("Transaction queue closed")
+    }
 
-                    self.message_builder
-                        .lock()
-                        .await
-                        .respond(
-                            chaincode_message::Type::Completed,
-                            response.encode_to_vec(),
-                            message,
-                        )
-                        .await;
+    /// Starts the chaincode function of a transaction. The function runs in
+    /// its own task, which sends the `Completed` response and releases
+    /// `permit` when it is done.
+    async fn dispatch(&self, message: ChaincodeMessage, permit: OwnedSemaphorePermit) {
+        tracing::debug!(tx_id = %message.txid, "Executing transaction");
+        let input = match ChaincodeInput::decode(message.payload.as_slice()) {
+            Ok(input) => input,
+            Err(err) => {
+                let error_text = format!("Invalid chaincode input; {}", err);
+                tracing::error!(tx_id = %message.txid, "{error_text}");
+                let mut message_builder = self.message_builder.lock().await;
+                let sent = match message_builder
+                    .send(chaincode_message::Type::Error, error_text.encode_to_vec())
+                    .await
+                {
+                    Ok(()) => {
+                        message_builder
+                            .send(
+                                chaincode_message::Type::Response,
+                                error_text.encode_to_vec(),
+                            )
+                            .await
+                    }
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = sent {
+                    tracing::error!(tx_id = %message.txid, "Error sending ERROR: {err}");
                 }
-                Err(err) => {
-                    let error_text = format!("Invalid chaincode input; {}", err);
-                    eprintln!("[MessageHandler] {}", error_text);
-                    self.message_builder
-                        .lock()
-                        .await
-                        .send(chaincode_message::Type::Error, error_text.encode_to_vec())
-                        .await;
-                    self.message_builder
-                        .lock()
-                        .await
-                        .send(
-                            chaincode_message::Type::Response,
-                            error_text.encode_to_vec(),
-                        )
-                        .await;
+                return;
+            }
+        };
+        let Some(peer_response_queue) = self.pending_transactions.register(&message) else {
+            let error_text = format!(
+                "[{}] Transaction {} is already executing",
+                message.channel_id, message.txid
+            );
+            tracing::error!(tx_id = %message.txid, "{error_text}");
+            let tx_id = message.txid.clone();
+            if let Err(err) = self
+                .message_builder
+                .lock()
+                .await
+                .respond(
+                    chaincode_message::Type::Error,
+                    error_text.into_bytes(),
+                    message,
+                )
+                .await
+            {
+                tracing::error!(%tx_id, "Error sending ERROR: {err}");
+            }
+            return;
+        };
+
+        //structname:functionname,arg1,arg2,arg3
+        //First argument is the contract:function
+        let arguments = match decode_arguments(&input) {
+            Ok(arguments) => arguments,
+            Err(error_text) => {
+                let response = Response {
+                    status: Status::BadRequest.into(),
+                    message: error_text,
+                    payload: vec![],
+                };
+                complete(
+                    &self.message_builder,
+                    &self.pending_transactions,
+                    message,
+                    response,
+                )
+                .await;
+                return;
+            }
+        };
+        let contract_function = arguments[0].split(":").collect::<Vec<&str>>();
+
+        let contract_name = if contract_function.len() > 1 {
+            contract_function
+                .first()
+                .expect("[MessageHandler] Expected contract_name but found nothing")
+        } else {
+            ""
+        };
+        let function_name = contract_function
+            .last()
+            .expect("[MessageHandler] Expected function_name but found nothing");
+        let function = match self.contracts.get(contract_name) {
+            Some(contract) => match contract.get(*function_name) {
+                Some(function) => function,
+                None => {
+                    let response = Response {
+                        status: Status::NotFound.into(),
+                        message: format!(
+                            "Function {function_name} not found in contract {contract_name} from chaincode {}",
+                            self.chaincode_id.name
+                        ),
+                        payload: vec![],
+                    };
+                    complete(
+                        &self.message_builder,
+                        &self.pending_transactions,
+                        message,
+                        response,
+                    )
+                    .await;
+                    return;
                 }
+            },
+            None => {
+                let response = Response {
+                    status: Status::NotFound.into(),
+                    message: format!(
+                        "Contract {contract_name} not found in chaincode {}",
+                        self.chaincode_id.name
+                    ),
+                    payload: vec![],
+                };
+                complete(
+                    &self.message_builder,
+                    &self.pending_transactions,
+                    message,
+                    response,
+                )
+                .await;
+                return;
             }
+        };
+
+        let span = tracing::info_span!(
+            "invoke",
+            tx_id = %message.txid,
+            channel = %message.channel_id,
+            contract = contract_name,
+            function = *function_name,
+            duration_ms = tracing::field::Empty,
+        );
+        let peer_response_queue = Arc::new(Mutex::new(peer_response_queue));
+        let execution = function.call(
+            Context::new(
+                self.message_builder.clone(),
+                message.clone(),
+                peer_response_queue.clone(),
+            ),
+            arguments.iter().skip(1).cloned().collect::<Vec<String>>(),
+        );
+        let message_builder = self.message_builder.clone();
+        let pending_transactions = self.pending_transactions.clone();
+        tokio::spawn(async move {
+            let result = telemetry::observe(span, "invoke", async {
+                match execution.await {
+                    Ok(result) => result,
+                    Err(err) => Err(ChaincodeError::internal(format!(
+                        "An error occurred during the exection of the chaincode function: {err}"
+                    ))),
+                }
+            })
+            .await;
+            // Holding the queue keeps the close of a dropped query iterator from
+            // being sent after COMPLETED.
+            let _peer_response_queue = peer_response_queue.lock().await;
+            complete(
+                &message_builder,
+                &pending_transactions,
+                message,
+                transaction_response(result),
+            )
+            .await;
+            drop(permit);
+        });
+    }
+}
+
+/// Sends the `Completed` response of a transaction and forgets it.
+async fn complete(
+    message_builder: &Mutex<MessageBuilder>,
+    pending_transactions: &PendingTransactions,
+    message: ChaincodeMessage,
+    response: Response,
+) {
+    pending_transactions.unregister(&message);
+    let tx_id = message.txid.clone();
+    if let Err(err) = message_builder
+        .lock()
+        .await
+        .respond(
+            chaincode_message::Type::Completed,
+            response.encode_to_vec(),
+            message,
+        )
+        .await
+    {
+        tracing::error!(%tx_id, "Error sending COMPLETED: {err}");
+    }
+}
+
+/// Decodes the arguments of a transaction. Fails with the reason if there is
+/// no `contract:function` argument or an argument is not valid UTF-8.
+fn decode_arguments(input: &ChaincodeInput) -> Result<Vec<String>, String> {
+    if input.args.is_empty() {
+        return Err("Expected the function name as first argument but found nothing".to_string());
+    }
+    input
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| {
+            String::from_utf8(argument.clone())
+                .map_err(|err| format!("Argument {index} is not valid UTF-8: {err}"))
+        })
+        .collect()
+}
+
+/// Turns the result of a chaincode function into the response for the peer.
+/// Errors keep their status so the client sees the endorsement fail.
+fn transaction_response(result: Result<String, ChaincodeError>) -> Response {
+    match result {
+        Ok(payload) => Response {
+            status: Status::Success.into(),
+            message: String::default(),
+            payload: payload.into_bytes(),
+        },
+        Err(err) => Response {
+            status: err.status,
+            message: err.message,
+            payload: vec![],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaincode::Callable;
+    use std::collections::HashMap;
+
+    /// Reads the key given as argument and returns its value.
+    struct ReadKey;
+
+    impl Callable for ReadKey {
+        fn call(
+            &self,
+            ctx: Context,
+            args: Vec<String>,
+        ) -> tokio::task::JoinHandle<Result<String, ChaincodeError>> {
+            tokio::spawn(async move {
+                let value = ctx.get_state_string(&args[0]).await?;
+                Ok(value.unwrap_or_default())
+            })
         }
-        eprintln!("[MessageHandler] Channel closed")
+
+        fn name(&self) -> &str {
+            "ReadKey"
+        }
+    }
+
+    fn transaction(txid: &str, key: &str) -> ChaincodeMessage {
+        ChaincodeMessage {
+            r#type: chaincode_message::Type::Transaction.into(),
+            txid: txid.to_string(),
+            channel_id: "mychannel".to_string(),
+            payload: ChaincodeInput {
+                args: vec![b"ReadKey".to_vec(), key.as_bytes().to_vec()],
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Both transactions must be running before the peer answers either, and
+    /// each must get its own response even when they arrive out of order.
+    #[tokio::test]
+    async fn executes_transactions_concurrently() {
+        let (outbound_tx, mut outbound_rx) = futures_channel::mpsc::channel(10);
+        let (mut transaction_tx, transaction_rx) = futures_channel::mpsc::channel(10);
+        let pending_transactions = PendingTransactions::default();
+        let function: Box<dyn Callable> = Box::new(ReadKey);
+        let handler = MessageHandler {
+            contracts: Arc::new(HashMap::from([(
+                String::new(),
+                HashMap::from([("ReadKey".to_string(), function)]),
+            )])),
+            message_builder: Arc::new(Mutex::new(MessageBuilder::new(outbound_tx))),
+            chaincode_id: ChaincodeId::default(),
+            transaction_queue: transaction_rx,
+            pending_transactions: pending_transactions.clone(),
+            concurrency_limit: Arc::new(Semaphore::new(2)),
+        };
+        tokio::spawn(handler.run());
+
+        transaction_tx.try_send(transaction("tx1", "a")).unwrap();
+        transaction_tx.try_send(transaction("tx2", "b")).unwrap();
+        for _ in 0..2 {
+            let request = outbound_rx.next().await.unwrap();
+            assert_eq!(request.r#type, chaincode_message::Type::GetState as i32);
+        }
+
+        for (txid, value) in [("tx2", "2"), ("tx1", "1")] {
+            let unrouted = pending_transactions.route(ChaincodeMessage {
+                r#type: chaincode_message::Type::Response.into(),
+                txid: txid.to_string(),
+                channel_id: "mychannel".to_string(),
+                payload: value.as_bytes().to_vec(),
+                ..Default::default()
+            });
+            assert!(unrouted.is_none());
+        }
+        let mut results = HashMap::new();
+        for _ in 0..2 {
+            let completed = outbound_rx.next().await.unwrap();
+            assert_eq!(completed.r#type, chaincode_message::Type::Completed as i32);
+            let response = Response::decode(completed.payload.as_slice()).unwrap();
+            results.insert(completed.txid, response.payload);
+        }
+        assert_eq!(results["tx1"], b"1");
+        assert_eq!(results["tx2"], b"2");
+    }
+
+    /// Malformed arguments fail the transaction with a 400 instead of
+    /// stopping the handler, which keeps executing further transactions.
+    #[tokio::test]
+    async fn rejects_malformed_arguments() {
+        let (outbound_tx, mut outbound_rx) = futures_channel::mpsc::channel(10);
+        let (mut transaction_tx, transaction_rx) = futures_channel::mpsc::channel(10);
+        let pending_transactions = PendingTransactions::default();
+        let handler = MessageHandler {
+            contracts: Arc::new(HashMap::new()),
+            message_builder: Arc::new(Mutex::new(MessageBuilder::new(outbound_tx))),
+            chaincode_id: ChaincodeId::default(),
+            transaction_queue: transaction_rx,
+            pending_transactions: pending_transactions.clone(),
+            concurrency_limit: Arc::new(Semaphore::new(1)),
+        };
+        tokio::spawn(handler.run());
+
+        let with_args = |txid: &str, args: Vec<Vec<u8>>| ChaincodeMessage {
+            payload: ChaincodeInput {
+                args,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            ..transaction(txid, "")
+        };
+        transaction_tx.try_send(with_args("tx1", vec![])).unwrap();
+        transaction_tx
+            .try_send(with_args("tx2", vec![b"ReadKey".to_vec(), vec![0xff]]))
+            .unwrap();
+        transaction_tx.try_send(transaction("tx3", "a")).unwrap();
+        for (txid, status) in [("tx1", 400), ("tx2", 400), ("tx3", 404)] {
+            let completed = outbound_rx.next().await.unwrap();
+            assert_eq!(completed.r#type, chaincode_message::Type::Completed as i32);
+            assert_eq!(completed.txid, txid);
+            let response = Response::decode(completed.payload.as_slice()).unwrap();
+            assert_eq!(response.status, status, "{}", response.message);
+        }
+        assert!(pending_transactions.route(transaction("tx1", "")).is_some());
+    }
+
+    #[test]
+    fn maps_results_to_responses() {
+        let response = transaction_response(Ok("{\"id\":1}".to_string()));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.payload, b"{\"id\":1}");
+
+        let response = transaction_response(Err(ChaincodeError::not_found("asset1 not found")));
+        assert_eq!(response.status, 404);
+        assert_eq!(response.message, "asset1 not found");
+
+        let err: Result<String, std::num::ParseIntError> =
+            "x".parse::<i32>().map(|_| String::new());
+        let response = transaction_response(err.map_err(ChaincodeError::from_error));
+        assert_eq!(response.status, 500);
+        assert_eq!(response.message, "invalid digit found in string");
+    }
+
+    #[test]
+    fn keeps_the_status_of_chaincode_errors() {
+        let err = ChaincodeError::from_error(ChaincodeError::bad_request("negative amount"));
+        assert_eq!(err, ChaincodeError::bad_request("negative amount"));
     }
 }
@@ -1,82 +1,69 @@
-use futures_channel::mpsc::Sender;
+use std::collections::HashMap;
 
-use crate::{
-    chaincode::Metadata,
-    fabric::protos::{ChaincodeEvent, ChaincodeMessage, chaincode_message},
-    gateway::chaincode::ChaincodeCallBuilder,
-    identity::IdentityBuilder,
-};
+use futures_channel::mpsc::{SendError, Sender};
+use futures_util::future::poll_fn;
+
+use crate::fabric::protos::{ChaincodeEvent, ChaincodeMessage, chaincode_message};
 
 pub struct MessageBuilder {
     tx: Sender<ChaincodeMessage>,
-    chaincode_call_builder: crate::gateway::chaincode::ChaincodeCallBuilder,
-    pending_event: Option<ChaincodeEvent>,
+    /// Events set by the executing transactions, keyed by channel and
+    /// transaction id.
+    pending_events: HashMap<(String, String), ChaincodeEvent>,
 }
 impl MessageBuilder {
-    pub fn new(metadata: &Metadata, tx: Sender<ChaincodeMessage>) -> MessageBuilder {
-        let identity = IdentityBuilder::from_pem(&metadata.root_cert.clone().into_bytes())
-            .expect("Invalid certificate")
-            .with_msp(metadata.mspid.clone())
-            .expect("Invalid msp")
-            .with_private_key(metadata.client_key.clone())
-            .expect("Invalid private key")
-            .build()
-            .expect("Could not build identity with provided data");
+    pub fn new(tx: Sender<ChaincodeMessage>) -> MessageBuilder {
         MessageBuilder {
             tx,
-            chaincode_call_builder: ChaincodeCallBuilder {
-                identity,
-                channel_name: None,
-                chaincode_id: Some(metadata.chaincode_id.clone()),
-                contract_id: None,
-                function_name: None,
-                system_chaincode: false,
-                function_args: vec![],
-                transient_map: std::collections::HashMap::default(),
-                endorsing_organizations: vec![],
-                proposal: None,
-                header: None,
-                nonce: None,
-                transaction_id: None,
-            },
-            pending_event: None,
+            pending_events: HashMap::new(),
         }
     }
 
-    /// Statically sets the pending chaincode event, to be attached to the
-    /// next `Completed` response. See
+    /// Sets the pending chaincode event of the transaction `event.tx_id` on
+    /// `channel_id`, to be attached to its `Completed` response. See
     /// [`Context::set_event`](crate::chaincode::context::Context::set_event).
     ///
-    /// Unlike [`with_proposal`](crate::gateway::chaincode::ChaincodeCallBuilder::with_proposal)
-    /// and friends, this is deliberately NOT reset after every [`respond`](Self::respond)
+    /// This is deliberately NOT reset after every [`respond`](Self::respond)
     /// call: a transaction may issue any number of intermediate GetState/PutState/etc.
     /// round trips between `set_event` and its final `Completed` response, and the
     /// event must survive all of them. `respond` only clears it once actually
-    /// consumed, on the `Completed` branch.
-    pub(crate) fn with_event(&mut self, event: Option<ChaincodeEvent>) -> &mut Self {
-        self.pending_event = event;
+    /// consumed, on the `Completed` branch. Events are kept per transaction, as
+    /// several transactions execute concurrently.
+    pub(crate) fn with_event(&mut self, channel_id: &str, event: ChaincodeEvent) -> &mut Self {
+        self.pending_events
+            .insert((channel_id.to_string(), event.tx_id.clone()), event);
         self
     }
 
-    pub async fn send(&mut self, r#type: chaincode_message::Type, payload: Vec<u8>) {
-        let message = self
-            .chaincode_call_builder
-            .generate_chaincode_message(r#type, payload)
-            .expect("Failed creating message");
-        self.tx.start_send(message).unwrap();
+    /// Sends a message that belongs to no transaction, like `REGISTER`. Waits
+    /// while the queue to the peer is full and fails once the stream to the
+    /// peer is closed.
+    pub async fn send(
+        &mut self,
+        r#type: chaincode_message::Type,
+        payload: Vec<u8>,
+    ) -> Result<(), SendError> {
+        let message = ChaincodeMessage {
+            r#type: r#type.into(),
+            timestamp: Some(std::time::SystemTime::now().into()),
+            payload,
+            ..Default::default()
+        };
+        self.start_send(message).await
     }
+    /// Sends a message of the transaction `message` belongs to. Like
+    /// [`send`](Self::send), waits while the queue to the peer is full.
     pub async fn respond(
         &mut self,
         r#type: chaincode_message::Type,
         payload: Vec<u8>,
         message: ChaincodeMessage,
-    ) {
+    ) -> Result<(), SendError> {
         let chaincode_event = match r#type {
-            chaincode_message::Type::Completed => {
-                let event = self.pending_event.clone().or(message.chaincode_event.clone());
-                self.with_event(None);
-                event
-            }
+            chaincode_message::Type::Completed => self
+                .pending_events
+                .remove(&(message.channel_id.clone(), message.txid.clone()))
+                .or(message.chaincode_event.clone()),
             _ => message.chaincode_event.clone(),
         };
         let message = ChaincodeMessage {
@@ -88,8 +75,12 @@ impl MessageBuilder {
             chaincode_event,
             channel_id: message.channel_id,
         };
-        self.tx.start_send(message).unwrap();
-        self.chaincode_call_builder.with_transaction_id(None);
-        self.chaincode_call_builder.with_proposal(None);
+        self.start_send(message).await
+    }
+
+    /// Waits for room in the queue to the peer, then sends `message`.
+    async fn start_send(&mut self, message: ChaincodeMessage) -> Result<(), SendError> {
+        poll_fn(|cx| self.tx.poll_ready(cx)).await?;
+        self.tx.start_send(message)
     }
 }
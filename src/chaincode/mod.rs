@@ -1,14 +1,16 @@
+pub mod cid;
 pub mod context;
 mod handler;
 mod message;
 mod router;
+mod server;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 pub use crate::prelude::derives;
+pub use server::ServerConfig;
 
-use crate::{chaincode::context::Context, fabric::protos::ChaincodeId};
-use prost::Message;
+use crate::{chaincode::context::Context, error::ChaincodeError, fabric::protos::ChaincodeId};
 use serde::{Deserialize, Serialize};
 
 /// connection.json representation provided by the peer
@@ -28,19 +30,35 @@ pub struct Metadata {
     pub root_cert: String,
 }
 
-pub trait Callable {
+pub trait Callable: Send + Sync {
     fn call(
         &self,
         ctx: Context,
         args: Vec<String>,
-    ) -> tokio::task::JoinHandle<Result<String, String>>;
+    ) -> tokio::task::JoinHandle<Result<String, ChaincodeError>>;
     fn name(&self) -> &str;
 }
 
+/// The functions of the registered contracts, by contract and function name.
+pub(crate) type Contracts = HashMap<String, HashMap<String, Box<dyn Callable>>>;
+
+/// Number of transactions a chaincode executes at the same time, unless set
+/// with [Launcher::with_max_concurrency].
+pub const DEFAULT_MAX_CONCURRENCY: usize = 100;
+
+/// How the chaincode and the peer connect.
+enum Connection {
+    /// The chaincode dials the peer, which launched it through a builder.
+    Peer(Metadata),
+    /// The peer dials the chaincode server (Chaincode-as-a-Service).
+    Server(ServerConfig),
+}
+
 pub struct Launcher {
-    metadata: Metadata,
+    connection: Connection,
     chaincode_id: ChaincodeId,
-    contracts: HashMap<String, HashMap<String, Box<dyn Callable>>>,
+    contracts: Contracts,
+    max_concurrency: usize,
 }
 
 impl Launcher {
@@ -77,31 +95,89 @@ impl Launcher {
         }
         self
     }
+    /// Sets the number of transactions executed at the same time. Up to as
+    /// many further transactions wait until one of them completes, the peer
+    /// receives an ERROR for any more. Defaults to [DEFAULT_MAX_CONCURRENCY].
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "max_concurrency must be at least 1");
+        self.max_concurrency = max_concurrency;
+        self
+    }
     pub fn launch(self) {
         tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .expect("Unable to start tokio runtime")
             .block_on(async {
-                let message_handler =
-                    handler::MessageHandler::new(&self.metadata, self.chaincode_id, self.contracts)
+                let contracts = Arc::new(self.contracts);
+                match self.connection {
+                    Connection::Peer(metadata) => {
+                        let (tx, rx) = futures_channel::mpsc::channel(100);
+                        let (message_handler, router) = handler::MessageHandler::new(
+                            self.chaincode_id,
+                            contracts,
+                            self.max_concurrency,
+                            tx,
+                        )
                         .await;
-                message_handler.run().await;
-                println!("Message handler exited");
+                        let stream = router::connect(&metadata, rx).await;
+                        tokio::spawn(async move {
+                            tracing::info!("Starting router");
+                            router.run(stream).await;
+                            tracing::info!("Router stopped");
+                        });
+                        message_handler.run().await;
+                        tracing::info!("Message handler exited");
+                    }
+                    Connection::Server(config) => {
+                        server::serve(config, self.chaincode_id, contracts, self.max_concurrency)
+                            .await
+                            .expect("Chaincode server failed");
+                        tracing::info!("Chaincode server stopped");
+                    }
+                }
             });
     }
 }
+/// Prepares the chaincode launch from the environment. If
+/// `CHAINCODE_SERVER_ADDRESS` is set, the chaincode runs as a server the peer
+/// connects to (Chaincode-as-a-Service), see [ServerConfig::from_env].
+/// Otherwise it dials the peer with the `METADATA` the peer's builder passes in.
+///
+/// Panics if the settings are missing or invalid, e.g. if TLS is not disabled
+/// and its certificate or key can't be read.
 pub fn initialize() -> Launcher {
+    if std::env::var(server::SERVER_ADDRESS).is_ok() {
+        let config = ServerConfig::from_env()
+            .unwrap_or_else(|err| panic!("Invalid chaincode server settings: {err}"));
+        return initialize_server(config);
+    }
     let metadata = std::env::var("METADATA").unwrap();
     let metadata =
         serde_json::from_str::<Metadata>(metadata.as_str()).expect("Invalid metadata json");
-    let vec = metadata.chaincode_id.clone().encode_to_vec();
-    let buf = vec.as_slice();
-    let chaincode_id = ChaincodeId::decode(buf).expect("No valid chaincode id");
+    let chaincode_id = ChaincodeId {
+        name: metadata.chaincode_id.clone(),
+        ..Default::default()
+    };
 
     Launcher {
-        metadata,
+        connection: Connection::Peer(metadata),
+        chaincode_id,
+        contracts: HashMap::new(),
+        max_concurrency: DEFAULT_MAX_CONCURRENCY,
+    }
+}
+/// Prepares the chaincode to run as a server the peer connects to
+/// (Chaincode-as-a-Service).
+pub fn initialize_server(config: ServerConfig) -> Launcher {
+    let chaincode_id = ChaincodeId {
+        name: config.chaincode_id.clone(),
+        ..Default::default()
+    };
+    Launcher {
+        connection: Connection::Server(config),
         chaincode_id,
         contracts: HashMap::new(),
+        max_concurrency: DEFAULT_MAX_CONCURRENCY,
     }
 }
@@ -1,93 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
 use futures_channel::mpsc::{Receiver, Sender};
-use futures_util::StreamExt;
-use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity, Uri};
+use futures_util::{Stream, StreamExt};
+use tonic::{
+    Status, Streaming,
+    transport::{Certificate, Channel, ClientTlsConfig, Identity, Uri},
+};
 
 use crate::{
-    chaincode::Metadata,
-    fabric::protos::{
-        ChaincodeMessage, chaincode_message,
-        chaincode_support_client::{self, ChaincodeSupportClient},
-    },
+    chaincode::{Metadata, message::MessageBuilder},
+    fabric::protos::{ChaincodeMessage, chaincode_message, chaincode_support_client},
 };
 
+/// Dials the peer's chaincode support endpoint and registers the chaincode
+/// stream. `outbound` carries the messages to the peer; the returned stream
+/// the ones from the peer.
+pub async fn connect(
+    metadata: &Metadata,
+    outbound: Receiver<ChaincodeMessage>,
+) -> Streaming<ChaincodeMessage> {
+    let root_cert = Certificate::from_pem(metadata.root_cert.as_bytes());
+
+    let client_identity =
+        Identity::from_pem(metadata.client_cert.clone(), metadata.client_key.clone());
+    let tls_config = ClientTlsConfig::new()
+        .ca_certificate(root_cert.clone())
+        .identity(client_identity.clone());
+
+    let uri_builder = Uri::builder()
+        .scheme("https")
+        .authority(metadata.peer_address.clone())
+        .path_and_query("/");
+    let channel = Channel::builder(uri_builder.build().expect("Invalid uri"))
+        .tls_config(tls_config.clone())
+        .expect("[Router] Invald TLS config")
+        .rate_limit(100, std::time::Duration::from_secs(1))
+        .concurrency_limit(256)
+        .connect()
+        .await
+        .expect("[Router] Couldn't start gRPC channel");
+
+    let mut client = chaincode_support_client::ChaincodeSupportClient::new(channel.clone());
+    client
+        .register(outbound)
+        .await
+        .expect("[Router] Failed to register contract")
+        .into_inner()
+}
+
+/// Sorts the messages of the peer into new transactions and responses to the
+/// ledger calls of executing ones.
 pub struct Router {
-    ///The stream for messages from the peer to the contract
-    rx: Receiver<ChaincodeMessage>,
-    ///The client connecting to the peer. It will hold the connection as long as this contract runs
-    client: ChaincodeSupportClient<Channel>,
     transaction_queue: Sender<ChaincodeMessage>,
-    peer_response_queue: Sender<ChaincodeMessage>,
+    pending_transactions: PendingTransactions,
+    /// Answers the transactions the queue has no room for.
+    message_builder: Arc<tokio::sync::Mutex<MessageBuilder>>,
 }
 impl Router {
-    pub async fn new(
-        metadata: &Metadata,
+    pub fn new(
         transaction_queue: Sender<ChaincodeMessage>,
-        peer_response_queue: Sender<ChaincodeMessage>,
-        rx: Receiver<ChaincodeMessage>,
+        pending_transactions: PendingTransactions,
+        message_builder: Arc<tokio::sync::Mutex<MessageBuilder>>,
     ) -> Self {
-        let root_cert = Certificate::from_pem(metadata.root_cert.as_bytes());
-
-        let client_identity =
-            Identity::from_pem(metadata.client_cert.clone(), metadata.client_key.clone());
-        let tls_config = ClientTlsConfig::new()
-            .ca_certificate(root_cert.clone())
-            .identity(client_identity.clone());
-
-        let uri_builder = Uri::builder()
-            .scheme("https")
-            .authority(metadata.peer_address.clone())
-            .path_and_query("/");
-        let channel = Channel::builder(uri_builder.build().expect("Invalid uri"))
-            .tls_config(tls_config.clone())
-            .expect("[Router] Invald TLS config")
-            .rate_limit(100, std::time::Duration::from_secs(1))
-            .concurrency_limit(256)
-            .connect()
-            .await
-            .expect("[Router] Couldn't start gRPC channel");
-
-        let client = chaincode_support_client::ChaincodeSupportClient::new(channel.clone());
-
         Router {
-            rx,
-            client,
             transaction_queue,
-            peer_response_queue,
+            pending_transactions,
+            message_builder,
         }
     }
-    pub async fn run(mut self) {
-        let mut res = self
-            .client
-            .register(self.rx)
-            .await
-            .expect("[Router] Failed to register contract")
-            .into_inner();
+    /// Routes the messages of `res` until the peer closes the stream.
+    pub async fn run(
+        mut self,
+        mut res: impl Stream<Item = Result<ChaincodeMessage, Status>> + Unpin,
+    ) {
         while let Some(result) = res.next().await {
             match result {
                 Ok(message) => match chaincode_message::Type::try_from(message.r#type) {
                     Ok(chaincode_message::Type::Registered) => {
-                        eprintln!("[Router] Received Registered -> Current state is ESTABLISHED");
+                        tracing::info!("Received REGISTERED, current state is ESTABLISHED");
                     }
                     Ok(chaincode_message::Type::Ready) => {
-                        eprintln!("[Router] Received ready -> Current state is READY");
-                        eprintln!("[Router] ready for invocations");
+                        tracing::info!("Received READY, current state is READY");
                     }
                     Ok(chaincode_message::Type::Error) => {
-                        eprintln!(
-                            "[Router] Received Error: -> {}",
-                            String::from_utf8_lossy(message.payload.as_slice())
-                        );
+                        // An ERROR answering a ledger call goes to the transaction
+                        // that made it, anything else can only be logged.
+                        if let Some(message) = self.pending_transactions.route(message) {
+                            tracing::error!(
+                                tx_id = %message.txid,
+                                "Received ERROR: {}",
+                                String::from_utf8_lossy(message.payload.as_slice())
+                            );
+                        }
                     }
                     Ok(chaincode_message::Type::Transaction) => {
-                        eprintln!("[Router] Received transaction {}", message.txid);
-                        if let Err(err) = self.transaction_queue.start_send(message) {
-                            eprintln!("[Router] Error seinding transaction into queue: {}", err);
-                        }
+                        tracing::debug!(tx_id = %message.txid, "Received TRANSACTION");
+                        self.queue_transaction(message).await;
                     }
                     Ok(chaincode_message::Type::Response) => {
-                        eprintln!("[Router] Received response for {}", message.txid);
-                        if let Err(err) = self.peer_response_queue.start_send(message) {
-                            eprintln!("[Router] Error seinding response into queue: {}", err);
+                        tracing::trace!(tx_id = %message.txid, "Received RESPONSE");
+                        if let Some(message) = self.pending_transactions.route(message) {
+                            tracing::warn!(
+                                tx_id = %message.txid,
+                                channel = %message.channel_id,
+                                "Received RESPONSE for an unknown transaction"
+                            );
                         }
                     }
                     _ => {
@@ -97,19 +117,188 @@ impl Router {
                                 "Unimplemented message type: {}",
                                 message_type.as_str_name()
                             );
-                            eprintln!("[Router] tx {}: {}", message.txid, error_text);
+                            tracing::warn!(tx_id = %message.txid, "{error_text}");
                         } else {
                             let error_text = format!("Unknown message type: {}", message.r#type);
-                            eprintln!("[Router] tx {}: {}", message.txid, error_text);
+                            tracing::warn!(tx_id = %message.txid, "{error_text}");
                         }
                     }
                 },
                 Err(err) => {
-                    let error_text =
-                        format!("[Router] Error receiving messages stream: Status {}", err);
-                    eprintln!("{}", error_text);
+                    tracing::error!("Error receiving messages stream: Status {err}");
                 }
             }
         }
     }
+
+    /// Hands a transaction to the handler. Waiting for room in a full queue
+    /// would stop the responses the running transactions wait for, so the
+    /// peer gets an ERROR for the transaction instead.
+    async fn queue_transaction(&mut self, message: ChaincodeMessage) {
+        let Err(err) = self.transaction_queue.try_send(message) else {
+            return;
+        };
+        let full = err.is_full();
+        let message = err.into_inner();
+        let error_text = if full {
+            "Too many transactions are waiting to be executed"
+        } else {
+            "The chaincode is shutting down"
+        };
+        tracing::error!(tx_id = %message.txid, "Rejecting transaction: {error_text}");
+        let tx_id = message.txid.clone();
+        if let Err(err) = self
+            .message_builder
+            .lock()
+            .await
+            .respond(
+                chaincode_message::Type::Error,
+                error_text.as_bytes().to_vec(),
+                message,
+            )
+            .await
+        {
+            tracing::error!(%tx_id, "Error sending ERROR: {err}");
+        }
+    }
+}
+
+type ResponseQueues = HashMap<(String, String), Sender<ChaincodeMessage>>;
+
+/// The transactions currently executing, with the queue each one receives the
+/// peer's responses to its ledger calls on. Transactions are keyed by channel
+/// and transaction id, as the same id can be in flight on two channels.
+#[derive(Clone, Default)]
+pub struct PendingTransactions {
+    queues: Arc<Mutex<ResponseQueues>>,
+}
+impl PendingTransactions {
+    /// Registers the transaction of `message` and returns the queue of its
+    /// responses, or `None` if the transaction is already executing.
+    pub fn register(&self, message: &ChaincodeMessage) -> Option<Receiver<ChaincodeMessage>> {
+        let mut queues = self.queues.lock().expect("Poisoned transaction map");
+        let key = (message.channel_id.clone(), message.txid.clone());
+        if queues.contains_key(&key) {
+            return None;
+        }
+        // A transaction waits for each response before it sends the next
+        // request, so the queue never holds more than one message.
+        let (sender, receiver) = futures_channel::mpsc::channel(1);
+        queues.insert(key, sender);
+        Some(receiver)
+    }
+
+    /// Removes a finished transaction. Closes its response queue.
+    pub fn unregister(&self, message: &ChaincodeMessage) {
+        self.queues
+            .lock()
+            .expect("Poisoned transaction map")
+            .remove(&(message.channel_id.clone(), message.txid.clone()));
+    }
+
+    /// Hands a response to the transaction it belongs to. Gives the message
+    /// back if that transaction is not executing.
+    pub fn route(&self, message: ChaincodeMessage) -> Option<ChaincodeMessage> {
+        let mut queues = self.queues.lock().expect("Poisoned transaction map");
+        match queues.get_mut(&(message.channel_id.clone(), message.txid.clone())) {
+            Some(queue) => queue.try_send(message).err().map(|err| err.into_inner()),
+            None => Some(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chaincode::{context::Context, message::MessageBuilder},
+        error::ChaincodeStubError,
+    };
+
+    fn message(channel_id: &str, txid: &str, payload: &[u8]) -> ChaincodeMessage {
+        ChaincodeMessage {
+            channel_id: channel_id.to_string(),
+            txid: txid.to_string(),
+            payload: payload.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn routes_responses_by_channel_and_transaction() {
+        let pending = PendingTransactions::default();
+        let mut first = pending.register(&message("ch1", "tx", b"")).unwrap();
+        let mut second = pending.register(&message("ch2", "tx", b"")).unwrap();
+        assert!(pending.register(&message("ch1", "tx", b"")).is_none());
+
+        assert_eq!(pending.route(message("ch2", "tx", b"2")), None);
+        assert_eq!(pending.route(message("ch1", "tx", b"1")), None);
+        assert_eq!(first.try_recv().unwrap().payload, b"1");
+        assert_eq!(second.try_recv().unwrap().payload, b"2");
+
+        let unknown = message("ch1", "other", b"");
+        assert_eq!(pending.route(unknown.clone()), Some(unknown));
+
+        pending.unregister(&message("ch1", "tx", b""));
+        assert!(first.try_recv().unwrap_err().is_closed());
+        assert!(pending.route(message("ch1", "tx", b"")).is_some());
+    }
+
+    /// A peer ERROR answering a ledger call has to reach the waiting call
+    /// instead of leaving the transaction hanging.
+    #[tokio::test]
+    async fn forwards_peer_errors_to_the_waiting_call() {
+        let pending = PendingTransactions::default();
+        let transaction = message("ch1", "tx", b"");
+        let responses = pending.register(&transaction).unwrap();
+        let (transaction_tx, _transaction_rx) = futures_channel::mpsc::channel(1);
+        let (outbound_tx, _outbound_rx) = futures_channel::mpsc::channel(1);
+        let message_builder = Arc::new(tokio::sync::Mutex::new(MessageBuilder::new(outbound_tx)));
+        let error = ChaincodeMessage {
+            r#type: chaincode_message::Type::Error.into(),
+            ..message("ch1", "tx", b"access denied")
+        };
+        Router::new(transaction_tx, pending, message_builder.clone())
+            .run(futures_util::stream::iter([Ok(error)]))
+            .await;
+
+        let context = Context::new(
+            message_builder,
+            transaction,
+            Arc::new(tokio::sync::Mutex::new(responses)),
+        );
+        assert_eq!(
+            context.get_state("key").await,
+            Err(ChaincodeStubError::PeerError("access denied".to_string()))
+        );
+    }
+
+    /// The router must not block on a full transaction queue, as it also
+    /// routes the responses the running transactions wait for.
+    #[tokio::test]
+    async fn rejects_transactions_beyond_the_queue() {
+        let (transaction_tx, mut transaction_rx) = futures_channel::mpsc::channel(0);
+        let (outbound_tx, mut outbound_rx) = futures_channel::mpsc::channel(1);
+        let router = Router::new(
+            transaction_tx,
+            PendingTransactions::default(),
+            Arc::new(tokio::sync::Mutex::new(MessageBuilder::new(outbound_tx))),
+        );
+        let transaction = |txid: &str| ChaincodeMessage {
+            r#type: chaincode_message::Type::Transaction.into(),
+            ..message("ch1", txid, b"")
+        };
+        router
+            .run(futures_util::stream::iter([
+                Ok(transaction("tx1")),
+                Ok(transaction("tx2")),
+            ]))
+            .await;
+
+        assert_eq!(transaction_rx.next().await.unwrap().txid, "tx1");
+        let rejected = outbound_rx.next().await.unwrap();
+        assert_eq!(rejected.r#type, chaincode_message::Type::Error as i32);
+        assert_eq!(rejected.txid, "tx2");
+        assert_eq!(rejected.channel_id, "ch1");
+    }
 }
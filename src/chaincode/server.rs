@@ -0,0 +1,340 @@
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    pin::Pin,
+    sync::Arc,
+};
+
+use futures_util::{Stream, StreamExt};
+use tonic::{
+    Request, Response, Status, Streaming,
+    transport::{Certificate, Identity, Server, ServerTlsConfig},
+};
+
+use crate::{
+    chaincode::{Contracts, handler::MessageHandler},
+    error::ChaincodeServerError,
+    fabric::protos::{
+        ChaincodeId, ChaincodeMessage,
+        chaincode_server::{Chaincode, ChaincodeServer},
+    },
+};
+
+pub(crate) const SERVER_ADDRESS: &str = "CHAINCODE_SERVER_ADDRESS";
+
+/// Settings of the chaincode server for Chaincode-as-a-Service, where the
+/// peer connects to an already running chaincode through the `ccaas` builder
+/// instead of launching it.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// The `host:port` the server listens on.
+    pub address: String,
+    /// The package ID the chaincode was installed with, e.g. `basic_1.0:<hash>`.
+    pub chaincode_id: String,
+    /// The PEM encoded TLS certificate of the server. The server runs without
+    /// TLS if this and `tls_key` are `None`.
+    pub tls_cert: Option<String>,
+    /// The PEM encoded private key of `tls_cert`.
+    pub tls_key: Option<String>,
+    /// The PEM encoded CA certificate of the peer's TLS client certificates.
+    /// If set, the peer has to authenticate with a client certificate.
+    pub client_ca_cert: Option<String>,
+}
+
+impl ServerConfig {
+    /// Reads the settings from the environment variables of the Fabric
+    /// chaincode shims:
+    /// * `CHAINCODE_SERVER_ADDRESS` - the `host:port` to listen on.
+    /// * `CHAINCODE_ID` - the package ID of the chaincode.
+    /// * `CHAINCODE_TLS_DISABLED` - `true` to serve without TLS.
+    /// * `CHAINCODE_TLS_CERT` and `CHAINCODE_TLS_KEY` - paths to the PEM
+    ///   encoded TLS certificate and key. Required unless TLS is disabled.
+    /// * `CHAINCODE_CLIENT_CA_CERT` - path to the PEM encoded CA certificate
+    ///   of the peer's client certificates, which enables mutual TLS.
+    pub fn from_env() -> Result<ServerConfig, ChaincodeServerError> {
+        let variable = |name: &str| {
+            std::env::var(name).map_err(|_| ChaincodeServerError::MissingVariable(name.to_string()))
+        };
+        let read_pem = |name: &str| {
+            let path = variable(name)?;
+            std::fs::read_to_string(&path).map_err(|err| {
+                ChaincodeServerError::IoError(format!("Couldn't read {name} at {path}: {err}"))
+            })
+        };
+        let address = variable(SERVER_ADDRESS)?;
+        let chaincode_id = variable("CHAINCODE_ID")?;
+        let tls_disabled = std::env::var("CHAINCODE_TLS_DISABLED")
+            .map(|disabled| disabled.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let (tls_cert, tls_key, client_ca_cert) = if tls_disabled {
+            (None, None, None)
+        } else {
+            let client_ca_cert = match std::env::var("CHAINCODE_CLIENT_CA_CERT") {
+                Ok(_) => Some(read_pem("CHAINCODE_CLIENT_CA_CERT")?),
+                Err(_) => None,
+            };
+            (
+                Some(read_pem("CHAINCODE_TLS_CERT")?),
+                Some(read_pem("CHAINCODE_TLS_KEY")?),
+                client_ca_cert,
+            )
+        };
+        Ok(ServerConfig {
+            address,
+            chaincode_id,
+            tls_cert,
+            tls_key,
+            client_ca_cert,
+        })
+    }
+
+    fn tls_config(&self) -> Result<Option<ServerTlsConfig>, ChaincodeServerError> {
+        let (cert, key) = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            (None, None) if self.client_ca_cert.is_none() => return Ok(None),
+            (None, None) => {
+                return Err(ChaincodeServerError::InvalidTls(
+                    "A client CA certificate requires a TLS certificate".to_string(),
+                ));
+            }
+            (Some(_), None) => {
+                return Err(ChaincodeServerError::InvalidTls(
+                    "A TLS certificate requires a TLS key".to_string(),
+                ));
+            }
+            (None, Some(_)) => {
+                return Err(ChaincodeServerError::InvalidTls(
+                    "A TLS key requires a TLS certificate".to_string(),
+                ));
+            }
+        };
+        let tls_config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        Ok(Some(match &self.client_ca_cert {
+            Some(client_ca_cert) => {
+                tls_config.client_ca_root(Certificate::from_pem(client_ca_cert))
+            }
+            None => tls_config,
+        }))
+    }
+
+    fn socket_address(&self) -> Result<SocketAddr, ChaincodeServerError> {
+        self.address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| ChaincodeServerError::InvalidAddress(self.address.clone()))
+    }
+}
+
+/// Runs the chaincode server until it fails. Every connection of a peer gets
+/// its own [MessageHandler] on the shared contracts.
+pub(crate) async fn serve(
+    config: ServerConfig,
+    chaincode_id: ChaincodeId,
+    contracts: Arc<Contracts>,
+    max_concurrency: usize,
+) -> Result<(), ChaincodeServerError> {
+    let transport_error =
+        |err: tonic::transport::Error| ChaincodeServerError::TransportError(err.to_string());
+    let mut server = Server::builder();
+    if let Some(tls_config) = config.tls_config()? {
+        server = server.tls_config(tls_config).map_err(transport_error)?;
+    }
+    let address = config.socket_address()?;
+    tracing::info!(%address, tls = config.tls_cert.is_some(), "Starting chaincode server");
+    server
+        .add_service(service(chaincode_id, contracts, max_concurrency))
+        .serve(address)
+        .await
+        .map_err(transport_error)
+}
+
+fn service(
+    chaincode_id: ChaincodeId,
+    contracts: Arc<Contracts>,
+    max_concurrency: usize,
+) -> ChaincodeServer<ChaincodeService> {
+    ChaincodeServer::new(ChaincodeService {
+        chaincode_id,
+        contracts,
+        max_concurrency,
+    })
+}
+
+type MessageStream = Pin<Box<dyn Stream<Item = Result<ChaincodeMessage, Status>> + Send>>;
+
+struct ChaincodeService {
+    chaincode_id: ChaincodeId,
+    contracts: Arc<Contracts>,
+    max_concurrency: usize,
+}
+
+#[tonic::async_trait]
+impl Chaincode for ChaincodeService {
+    type ConnectStream = MessageStream;
+
+    async fn connect(
+        &self,
+        request: Request<Streaming<ChaincodeMessage>>,
+    ) -> Result<Response<MessageStream>, Status> {
+        let remote_address = request.remote_addr();
+        tracing::info!(?remote_address, "Peer connected");
+        let (tx, rx) = futures_channel::mpsc::channel(100);
+        let (message_handler, router) = MessageHandler::new(
+            self.chaincode_id.clone(),
+            self.contracts.clone(),
+            self.max_concurrency,
+            tx,
+        )
+        .await;
+        let stream = request.into_inner();
+        tokio::spawn(async move {
+            router.run(stream).await;
+            tracing::info!(?remote_address, "Peer disconnected");
+        });
+        tokio::spawn(message_handler.run());
+        Ok(Response::new(Box::pin(rx.map(Ok))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fabric::protos::{
+        ChaincodeInput, chaincode_client::ChaincodeClient, chaincode_message,
+    };
+    use prost::Message;
+    use std::collections::HashMap;
+    use tonic::transport::{Channel, server::TcpIncoming};
+
+    #[tokio::test]
+    async fn serves_peer_connections() {
+        let chaincode_id = ChaincodeId {
+            name: "basic_1.0:1234".to_string(),
+            ..Default::default()
+        };
+        let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let address = incoming.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(service(chaincode_id.clone(), Arc::new(HashMap::new()), 10))
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = Channel::from_shared(format!("http://{address}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = ChaincodeClient::new(channel);
+        let (mut peer_tx, peer_rx) = futures_channel::mpsc::channel(10);
+        let mut stream = client.connect(peer_rx).await.unwrap().into_inner();
+
+        let register = stream.message().await.unwrap().unwrap();
+        assert_eq!(register.r#type, chaincode_message::Type::Register as i32);
+        assert_eq!(
+            ChaincodeId::decode(register.payload.as_slice()).unwrap(),
+            chaincode_id
+        );
+
+        peer_tx
+            .try_send(ChaincodeMessage {
+                r#type: chaincode_message::Type::Transaction.into(),
+                txid: "tx1".to_string(),
+                channel_id: "mychannel".to_string(),
+                payload: ChaincodeInput {
+                    args: vec![b"Missing".to_vec()],
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+                ..Default::default()
+            })
+            .unwrap();
+        let completed = stream.message().await.unwrap().unwrap();
+        assert_eq!(completed.r#type, chaincode_message::Type::Completed as i32);
+        assert_eq!(completed.txid, "tx1");
+        let response =
+            crate::fabric::protos::Response::decode(completed.payload.as_slice()).unwrap();
+        assert_eq!(response.status, 404);
+    }
+
+    fn config(tls_cert: Option<&str>, tls_key: Option<&str>) -> ServerConfig {
+        ServerConfig {
+            address: "127.0.0.1:0".to_string(),
+            chaincode_id: "basic_1.0:1234".to_string(),
+            tls_cert: tls_cert.map(str::to_string),
+            tls_key: tls_key.map(str::to_string),
+            client_ca_cert: None,
+        }
+    }
+
+    #[test]
+    fn rejects_incomplete_tls_settings() {
+        assert!(config(None, None).tls_config().unwrap().is_none());
+        assert!(
+            config(Some("cert"), Some("key"))
+                .tls_config()
+                .unwrap()
+                .is_some()
+        );
+        for incomplete in [
+            config(Some("cert"), None),
+            config(None, Some("key")),
+            ServerConfig {
+                client_ca_cert: Some("ca".to_string()),
+                ..config(None, None)
+            },
+        ] {
+            assert!(matches!(
+                incomplete.tls_config(),
+                Err(ChaincodeServerError::InvalidTls(_))
+            ));
+        }
+    }
+
+    /// TLS is only off when disabled explicitly, missing or unreadable files
+    /// are errors.
+    #[test]
+    fn reads_the_environment_failing_closed() {
+        let dir = std::env::temp_dir().join(format!("ccaas-env-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cert.pem"), "cert").unwrap();
+        std::fs::write(dir.join("key.pem"), "key").unwrap();
+        // SAFETY: no other test reads or writes the CHAINCODE_* variables.
+        let set = |name: &str, value: Option<String>| unsafe {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        };
+        let path = |file: &str| Some(dir.join(file).to_string_lossy().into_owned());
+        set(SERVER_ADDRESS, Some("0.0.0.0:9999".to_string()));
+        set("CHAINCODE_ID", Some("basic_1.0:1234".to_string()));
+
+        assert!(matches!(
+            ServerConfig::from_env(),
+            Err(ChaincodeServerError::MissingVariable(variable)) if variable == "CHAINCODE_TLS_CERT"
+        ));
+        set("CHAINCODE_TLS_CERT", path("cert.pem"));
+        set("CHAINCODE_TLS_KEY", path("missing.pem"));
+        assert!(matches!(
+            ServerConfig::from_env(),
+            Err(ChaincodeServerError::IoError(_))
+        ));
+        set("CHAINCODE_TLS_KEY", path("key.pem"));
+        let config = ServerConfig::from_env().unwrap();
+        assert_eq!(config.tls_cert.as_deref(), Some("cert"));
+        assert_eq!(config.tls_key.as_deref(), Some("key"));
+        assert_eq!(config.client_ca_cert, None);
+
+        set("CHAINCODE_TLS_DISABLED", Some("TRUE".to_string()));
+        set("CHAINCODE_TLS_CERT", None);
+        set("CHAINCODE_TLS_KEY", None);
+        let config = ServerConfig::from_env().unwrap();
+        assert_eq!((config.tls_cert, config.tls_key), (None, None));
+
+        for name in [SERVER_ADDRESS, "CHAINCODE_ID", "CHAINCODE_TLS_DISABLED"] {
+            set(name, None);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,433 @@
+//! Fabric common connection profiles.
+//!
+//! A connection profile describes the organizations of a network together with
+//! their peers, orderers and certificate authorities, as written by the
+//! `test-network` or exported from operations consoles. Profiles can be JSON or
+//! YAML, and TLS CA certificates can be inline PEM or paths relative to the
+//! profile.
+//!
+//! ```no_run
+//! use fabric_sdk::connection_profile::ConnectionProfile;
+//!
+//! # async fn example(identity: fabric_sdk::identity::Identity) -> Result<(), Box<dyn std::error::Error>> {
+//! let profile = ConnectionProfile::from_file("connection-org1.yaml")?;
+//! let mut client = profile.organization_client("Org1", identity)?;
+//! client.connect().await?;
+//!
+//! let ca = profile.ca_client("ca.org1.example.com")?;
+//! let info = ca.get_ca_info().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    error::ConnectionProfileError,
+    fabric_ca::{FabricCAClient, FabricCAClientBuilder},
+    gateway::client::{Client, ClientBuilder},
+    identity::Identity,
+};
+
+/// `grpcOptions` keys that name the host to verify TLS certificates against.
+const TLS_DOMAIN_NAME_OPTIONS: [&str; 2] = ["ssl-target-name-override", "hostnameOverride"];
+
+/// A parsed connection profile.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConnectionProfile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub client: Option<ClientConfig>,
+    #[serde(default)]
+    pub organizations: BTreeMap<String, Organization>,
+    #[serde(default)]
+    pub peers: BTreeMap<String, Node>,
+    #[serde(default)]
+    pub orderers: BTreeMap<String, Node>,
+    #[serde(default, rename = "certificateAuthorities")]
+    pub certificate_authorities: BTreeMap<String, CertificateAuthority>,
+    /// The directory relative TLS certificate paths are resolved against.
+    #[serde(skip)]
+    base_dir: Option<PathBuf>,
+}
+
+/// The `client` section, naming the organization the application belongs to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientConfig {
+    pub organization: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Organization {
+    #[serde(rename = "mspid")]
+    pub msp_id: String,
+    #[serde(default)]
+    pub peers: Vec<String>,
+    #[serde(default, rename = "certificateAuthorities")]
+    pub certificate_authorities: Vec<String>,
+}
+
+/// A peer or orderer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Node {
+    /// The gRPC endpoint, e.g. `grpcs://localhost:7051`.
+    pub url: String,
+    #[serde(default, rename = "tlsCACerts")]
+    pub tls_ca_certs: Option<TlsCaCerts>,
+    #[serde(default, rename = "grpcOptions")]
+    pub grpc_options: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CertificateAuthority {
+    /// The REST endpoint, e.g. `https://localhost:7054`.
+    pub url: String,
+    /// The name of the CA within the server, to pass in enrollment options.
+    #[serde(default, rename = "caName")]
+    pub ca_name: Option<String>,
+    #[serde(default, rename = "tlsCACerts")]
+    pub tls_ca_certs: Option<TlsCaCerts>,
+    #[serde(default, rename = "httpOptions")]
+    pub http_options: HashMap<String, serde_json::Value>,
+}
+
+/// TLS CA certificates, given inline, as a file or both.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsCaCerts {
+    /// Inline PEM certificates. Profiles use a single string or a list.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub pem: Vec<String>,
+    pub path: Option<PathBuf>,
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(pem)) => vec![pem],
+        Some(OneOrMany::Many(pems)) => pems,
+        None => vec![],
+    })
+}
+
+impl Node {
+    /// The name to verify the TLS certificate against, if overridden in the
+    /// gRPC options.
+    pub fn tls_domain_name(&self) -> Option<&str> {
+        TLS_DOMAIN_NAME_OPTIONS
+            .iter()
+            .find_map(|option| self.grpc_options.get(*option)?.as_str())
+    }
+}
+
+impl ConnectionProfile {
+    /// Reads a profile, as JSON if the file ends in `.json` and as YAML
+    /// otherwise. Relative TLS certificate paths are resolved against the
+    /// profile's directory.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConnectionProfileError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|err| {
+            ConnectionProfileError::IoError(format!("{}: {}", path.display(), err))
+        })?;
+        let mut profile = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&bytes)?
+        } else {
+            Self::from_yaml(&bytes)?
+        };
+        profile.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(profile)
+    }
+
+    /// Parses a JSON profile. Relative paths are resolved against the working
+    /// directory.
+    pub fn from_json(json: &[u8]) -> Result<Self, ConnectionProfileError> {
+        serde_json::from_slice(json)
+            .map_err(|err| ConnectionProfileError::ParseError(err.to_string()))
+    }
+
+    /// Parses a YAML profile. Relative paths are resolved against the working
+    /// directory.
+    pub fn from_yaml(yaml: &[u8]) -> Result<Self, ConnectionProfileError> {
+        serde_yaml::from_slice(yaml)
+            .map_err(|err| ConnectionProfileError::ParseError(err.to_string()))
+    }
+
+    /// Returns the organization of the `client` section, if any.
+    pub fn client_organization(&self) -> Option<&str> {
+        self.client.as_ref()?.organization.as_deref()
+    }
+
+    /// Looks up an organization by name or MSP ID.
+    pub fn organization(&self, name: &str) -> Result<&Organization, ConnectionProfileError> {
+        self.organizations
+            .get(name)
+            .or_else(|| {
+                self.organizations
+                    .values()
+                    .find(|organization| organization.msp_id == name)
+            })
+            .ok_or_else(|| not_found("Organization", name))
+    }
+
+    pub fn peer(&self, name: &str) -> Result<&Node, ConnectionProfileError> {
+        self.peers.get(name).ok_or_else(|| not_found("Peer", name))
+    }
+
+    pub fn orderer(&self, name: &str) -> Result<&Node, ConnectionProfileError> {
+        self.orderers
+            .get(name)
+            .ok_or_else(|| not_found("Orderer", name))
+    }
+
+    pub fn certificate_authority(
+        &self,
+        name: &str,
+    ) -> Result<&CertificateAuthority, ConnectionProfileError> {
+        self.certificate_authorities
+            .get(name)
+            .ok_or_else(|| not_found("Certificate authority", name))
+    }
+
+    /// Returns the TLS CA certificates as one PEM bundle, reading them from
+    /// disk if given as a path.
+    pub fn read_tls_ca_certs(&self, certs: &TlsCaCerts) -> Result<Vec<u8>, ConnectionProfileError> {
+        let mut bundle = certs.pem.join("\n").into_bytes();
+        if let Some(path) = &certs.path {
+            let path = match &self.base_dir {
+                Some(base_dir) if path.is_relative() => base_dir.join(path),
+                _ => path.clone(),
+            };
+            let pem = std::fs::read(&path).map_err(|err| {
+                ConnectionProfileError::IoError(format!("{}: {}", path.display(), err))
+            })?;
+            if !bundle.is_empty() {
+                bundle.push(b'\n');
+            }
+            bundle.extend(pem);
+        }
+        Ok(bundle)
+    }
+
+    /// Returns a builder configured with the peer's address and TLS settings.
+    /// Only the identity is left to set.
+    pub fn peer_client_builder(&self, peer: &str) -> Result<ClientBuilder, ConnectionProfileError> {
+        let node = self.peer(peer)?;
+        let (scheme, authority) = split_url(&node.url)?;
+        #[allow(unused_mut)]
+        let mut builder = ClientBuilder::new()
+            .with_scheme(grpc_scheme(scheme))?
+            .with_authority(authority)?;
+        #[cfg(not(feature = "client-wasm"))]
+        {
+            if let Some(certs) = &node.tls_ca_certs {
+                builder = builder.with_tls(self.read_tls_ca_certs(certs)?)?;
+            }
+            if let Some(domain_name) = node.tls_domain_name() {
+                builder = builder.with_tls_domain_name(domain_name)?;
+            }
+        }
+        Ok(builder)
+    }
+
+    /// Builds a client for the given peer. It still has to be connected.
+    pub fn peer_client(
+        &self,
+        peer: &str,
+        identity: Identity,
+    ) -> Result<Client, ConnectionProfileError> {
+        Ok(self
+            .peer_client_builder(peer)?
+            .with_identity(identity)?
+            .build()?)
+    }
+
+    /// Builds a client for the first peer of the organization, given by name
+    /// or MSP ID.
+    pub fn organization_client(
+        &self,
+        organization: &str,
+        identity: Identity,
+    ) -> Result<Client, ConnectionProfileError> {
+        let peer = self
+            .organization(organization)?
+            .peers
+            .first()
+            .ok_or_else(|| not_found("A peer of organization", organization))?;
+        self.peer_client(peer, identity)
+    }
+
+    /// Returns a builder configured with the CA's URL and TLS settings. An
+    /// identity is only needed for requests other than enrollment.
+    pub fn ca_client_builder(
+        &self,
+        ca: &str,
+    ) -> Result<FabricCAClientBuilder, ConnectionProfileError> {
+        let authority = self.certificate_authority(ca)?;
+        #[allow(unused_mut)]
+        let mut builder = FabricCAClientBuilder::new().with_url(&authority.url)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(certs) = &authority.tls_ca_certs {
+                builder = builder.with_tls(self.read_tls_ca_certs(certs)?);
+            }
+            let verify = authority
+                .http_options
+                .get("verify")
+                .and_then(serde_json::Value::as_bool);
+            if verify == Some(false) {
+                builder = builder.danger_accept_invalid_certs();
+            }
+        }
+        Ok(builder)
+    }
+
+    /// Builds a client for the CA without identity, e.g. to enroll.
+    pub fn ca_client(&self, ca: &str) -> Result<FabricCAClient, ConnectionProfileError> {
+        Ok(self.ca_client_builder(ca)?.build()?)
+    }
+
+    /// Returns a gRPC endpoint for the orderer with its TLS settings, e.g. to
+    /// create an `AtomicBroadcastClient`. Connect it with
+    /// [tonic::transport::Endpoint::connect].
+    #[cfg(not(feature = "client-wasm"))]
+    pub fn orderer_endpoint(
+        &self,
+        orderer: &str,
+    ) -> Result<tonic::transport::Endpoint, ConnectionProfileError> {
+        let node = self.orderer(orderer)?;
+        let (scheme, authority) = split_url(&node.url)?;
+        let invalid = |err: &dyn std::fmt::Display| {
+            ConnectionProfileError::ParseError(format!("{}: {}", node.url, err))
+        };
+        let mut endpoint = tonic::transport::Endpoint::from_shared(format!(
+            "{}://{}",
+            grpc_scheme(scheme),
+            authority
+        ))
+        .map_err(|err| invalid(&err))?;
+        if let Some(certs) = &node.tls_ca_certs {
+            let mut tls_config = tonic::transport::ClientTlsConfig::new().ca_certificate(
+                tonic::transport::Certificate::from_pem(self.read_tls_ca_certs(certs)?),
+            );
+            if let Some(domain_name) = node.tls_domain_name() {
+                tls_config = tls_config.domain_name(domain_name);
+            }
+            endpoint = endpoint
+                .tls_config(tls_config)
+                .map_err(|err| invalid(&err))?;
+        }
+        Ok(endpoint)
+    }
+}
+
+fn not_found(kind: &str, name: &str) -> ConnectionProfileError {
+    ConnectionProfileError::NotFound(format!("{} {}", kind, name))
+}
+
+/// Splits `grpcs://localhost:7051` into scheme and authority.
+fn split_url(url: &str) -> Result<(&str, &str), ConnectionProfileError> {
+    let (scheme, rest) = url.trim().split_once("://").ok_or_else(|| {
+        ConnectionProfileError::ParseError(format!("URL without scheme: {}", url))
+    })?;
+    Ok((scheme, rest.trim_end_matches('/')))
+}
+
+/// Maps the `grpc`/`grpcs` schemes of profiles to the HTTP schemes tonic uses.
+fn grpc_scheme(scheme: &str) -> &str {
+    match scheme {
+        "grpcs" => "https",
+        "grpc" => "http",
+        scheme => scheme,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityBuilder;
+
+    const RESOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources");
+
+    fn identity() -> Identity {
+        IdentityBuilder::from_pem(include_str!("../tests/resources/pki/user1-cert.pem"))
+            .unwrap()
+            .with_msp("Org1MSP")
+            .unwrap()
+            .with_private_key(include_str!("../tests/resources/pki/user1-key.pem"))
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_yaml_profiles() {
+        let profile =
+            ConnectionProfile::from_file(format!("{}/connection-org1.yaml", RESOURCES)).unwrap();
+        assert_eq!(profile.name, "test-network-org1");
+        assert_eq!(profile.client_organization(), Some("Org1"));
+        assert_eq!(
+            profile.organization("Org1MSP").unwrap().peers,
+            ["peer0.org1.example.com"]
+        );
+
+        let peer = profile.peer("peer0.org1.example.com").unwrap();
+        assert_eq!(peer.tls_domain_name(), Some("peer0.org1.example.com"));
+        let tls = profile
+            .read_tls_ca_certs(peer.tls_ca_certs.as_ref().unwrap())
+            .unwrap();
+        assert_eq!(tls, include_bytes!("../tests/resources/pki/ca-cert.pem"));
+
+        let client = profile.organization_client("Org1", identity()).unwrap();
+        assert_eq!(
+            client.tonic_connection.host.to_string(),
+            "https://localhost:7051/"
+        );
+
+        let endpoint = profile.orderer_endpoint("orderer.example.com").unwrap();
+        assert_eq!(endpoint.uri().to_string(), "https://localhost:7050/");
+        profile.ca_client("ca.org1.example.com").unwrap();
+
+        assert!(matches!(
+            profile.peer_client("peer0.org2.example.com", identity()),
+            Err(ConnectionProfileError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reads_json_profiles_with_inline_certificates() {
+        let profile =
+            ConnectionProfile::from_file(format!("{}/connection-org1.json", RESOURCES)).unwrap();
+        let ca = profile
+            .certificate_authority("ca.org1.example.com")
+            .unwrap();
+        assert_eq!(ca.ca_name.as_deref(), Some("ca-org1"));
+        let tls = profile
+            .read_tls_ca_certs(ca.tls_ca_certs.as_ref().unwrap())
+            .unwrap();
+        assert!(tls.starts_with(b"-----BEGIN CERTIFICATE-----"));
+        profile.ca_client("ca.org1.example.com").unwrap();
+
+        let client = profile
+            .peer_client("peer0.org1.example.com", identity())
+            .unwrap();
+        assert_eq!(
+            client.tonic_connection.host.to_string(),
+            "https://localhost:7051/"
+        );
+
+        assert!(matches!(
+            ConnectionProfile::from_json(b"{\"peers\": {\"peer0\": {}}}"),
+            Err(ConnectionProfileError::ParseError(_))
+        ));
+    }
+}
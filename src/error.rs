@@ -2,6 +2,10 @@
 pub enum BuilderError {
     InvalidParameter(String),
     MissingParameter(String),
+    InvalidPrivateKey(String),
+    InvalidCertificate(String),
+    /// The private key does not belong to the certificate's public key.
+    KeyMismatch,
 }
 
 impl std::error::Error for BuilderError {}
@@ -13,6 +17,13 @@ impl std::fmt::Display for BuilderError {
             BuilderError::MissingParameter(parameter) => {
                 write!(f, "Missing parameter: {}", parameter)
             }
+            BuilderError::InvalidPrivateKey(reason) => write!(f, "Invalid private key: {}", reason),
+            BuilderError::InvalidCertificate(reason) => {
+                write!(f, "Invalid certificate: {}", reason)
+            }
+            BuilderError::KeyMismatch => {
+                write!(f, "Private key does not match the certificate")
+            }
         }
     }
 }
@@ -128,3 +139,261 @@ impl std::fmt::Display for SubmitError {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum PolicyError {
+    /// The policy string is malformed. `position` is the byte offset into the input.
+    Syntax {
+        position: usize,
+        reason: String,
+    },
+    InvalidPrincipal(String),
+    InvalidThreshold(String),
+    InvalidEnvelope(String),
+}
+
+impl std::error::Error for PolicyError {}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Syntax { position, reason } => {
+                write!(f, "Invalid policy at position {}: {}", position, reason)
+            }
+            PolicyError::InvalidPrincipal(err) => write!(f, "Invalid principal: {}", err),
+            PolicyError::InvalidThreshold(err) => write!(f, "Invalid threshold: {}", err),
+            PolicyError::InvalidEnvelope(err) => write!(f, "Invalid policy envelope: {}", err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    DecodeError(&'static str),
+    /// The message carries no signature or signer.
+    MissingSignature,
+    InvalidIdentity(String),
+    InvalidSignature(String),
+    /// The signature is valid but not in the low-S form Fabric requires.
+    HighS,
+    UntrustedCertificate(String),
+}
+
+impl std::error::Error for VerifyError {}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::DecodeError(err) => write!(f, "Failed decoding struct: {}", err),
+            VerifyError::MissingSignature => write!(f, "Message is not signed"),
+            VerifyError::InvalidIdentity(err) => write!(f, "Invalid signer identity: {}", err),
+            VerifyError::InvalidSignature(err) => write!(f, "Invalid signature: {}", err),
+            VerifyError::HighS => write!(f, "Signature is not normalized to low-S"),
+            VerifyError::UntrustedCertificate(err) => {
+                write!(f, "Untrusted certificate: {}", err)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WalletError {
+    InvalidLabel(String),
+    IoError(String),
+    InvalidFormat(String),
+    /// Only `X.509` identities can be stored; idemix is not supported.
+    UnsupportedType(String),
+    BuilderError(BuilderError),
+}
+
+impl std::error::Error for WalletError {}
+
+impl std::fmt::Display for WalletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalletError::InvalidLabel(label) => write!(f, "Invalid wallet label: {}", label),
+            WalletError::IoError(err) => write!(f, "Wallet IO error: {}", err),
+            WalletError::InvalidFormat(err) => write!(f, "Invalid wallet entry: {}", err),
+            WalletError::UnsupportedType(identity_type) => {
+                write!(f, "Unsupported identity type: {}", identity_type)
+            }
+            WalletError::BuilderError(err) => write!(f, "Builder error: {}", err),
+        }
+    }
+}
+
+impl From<BuilderError> for WalletError {
+    fn from(err: BuilderError) -> Self {
+        WalletError::BuilderError(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConnectionProfileError {
+    IoError(String),
+    ParseError(String),
+    /// The profile has no peer, orderer, organization or CA of that name.
+    NotFound(String),
+    BuilderError(BuilderError),
+}
+
+impl std::error::Error for ConnectionProfileError {}
+
+impl std::fmt::Display for ConnectionProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionProfileError::IoError(err) => {
+                write!(f, "Couldn't read connection profile: {}", err)
+            }
+            ConnectionProfileError::ParseError(err) => {
+                write!(f, "Invalid connection profile: {}", err)
+            }
+            ConnectionProfileError::NotFound(name) => {
+                write!(f, "{} is not defined in the connection profile", name)
+            }
+            ConnectionProfileError::BuilderError(err) => write!(f, "Builder error: {}", err),
+        }
+    }
+}
+
+impl From<BuilderError> for ConnectionProfileError {
+    fn from(err: BuilderError) -> Self {
+        ConnectionProfileError::BuilderError(err)
+    }
+}
+
+/// An error of the chaincode server used for Chaincode-as-a-Service.
+#[derive(Debug)]
+pub enum ChaincodeServerError {
+    /// A required environment variable is not set.
+    MissingVariable(String),
+    IoError(String),
+    /// The TLS settings are incomplete or invalid.
+    InvalidTls(String),
+    InvalidAddress(String),
+    TransportError(String),
+}
+
+impl std::error::Error for ChaincodeServerError {}
+
+impl std::fmt::Display for ChaincodeServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChaincodeServerError::MissingVariable(variable) => {
+                write!(f, "Environment variable {} is not set", variable)
+            }
+            ChaincodeServerError::IoError(err) => write!(f, "IO error: {}", err),
+            ChaincodeServerError::InvalidTls(err) => write!(f, "Invalid TLS settings: {}", err),
+            ChaincodeServerError::InvalidAddress(address) => {
+                write!(f, "Invalid chaincode server address: {}", address)
+            }
+            ChaincodeServerError::TransportError(err) => write!(f, "Transport error: {}", err),
+        }
+    }
+}
+
+/// An error returned by a chaincode function, sent to the client as a
+/// `Response` with the given status instead of the default `500`. Fabric
+/// treats statuses of `400` and above as errors, so endorsement fails with
+/// the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaincodeError {
+    pub status: i32,
+    pub message: String,
+}
+
+impl ChaincodeError {
+    pub fn new(status: i32, message: impl Into<String>) -> Self {
+        ChaincodeError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(403, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(404, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(500, message)
+    }
+
+    /// Converts the error of a `#[transaction]` function. A [ChaincodeError]
+    /// keeps its status, any other error becomes a `500` with its message.
+    pub fn from_error<E: std::fmt::Display + 'static>(err: E) -> Self {
+        match (&err as &dyn std::any::Any).downcast_ref::<ChaincodeError>() {
+            Some(err) => err.clone(),
+            None => Self::internal(err.to_string()),
+        }
+    }
+}
+
+impl std::error::Error for ChaincodeError {}
+
+impl std::fmt::Display for ChaincodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for ChaincodeError {
+    fn from(message: String) -> Self {
+        ChaincodeError::internal(message)
+    }
+}
+
+impl From<&str> for ChaincodeError {
+    fn from(message: &str) -> Self {
+        ChaincodeError::internal(message)
+    }
+}
+
+/// An error of a ledger call on the chaincode `Context`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChaincodeStubError {
+    /// The peer answered the call with an `ERROR` message.
+    PeerError(String),
+    DecodeError(String),
+    InvalidUtf8(String),
+    /// A key is not a valid composite key or a simple key uses the composite
+    /// key prefix.
+    InvalidKey(String),
+    /// A value could not be serialized to or deserialized from JSON.
+    JsonError(String),
+    /// The connection to the peer was closed before it answered.
+    ChannelClosed,
+    /// The chaincode message carries no signed proposal.
+    MissingProposal,
+}
+
+impl std::error::Error for ChaincodeStubError {}
+
+impl std::fmt::Display for ChaincodeStubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChaincodeStubError::PeerError(err) => write!(f, "Peer error: {}", err),
+            ChaincodeStubError::DecodeError(err) => write!(f, "Decode error: {}", err),
+            ChaincodeStubError::InvalidUtf8(err) => write!(f, "Invalid UTF-8 encoding: {}", err),
+            ChaincodeStubError::InvalidKey(err) => write!(f, "Invalid key: {}", err),
+            ChaincodeStubError::JsonError(err) => write!(f, "JSON error: {}", err),
+            ChaincodeStubError::ChannelClosed => {
+                write!(f, "The connection to the peer has been closed")
+            }
+            ChaincodeStubError::MissingProposal => write!(f, "No signed proposal found"),
+        }
+    }
+}
+
+impl From<ChaincodeStubError> for ChaincodeError {
+    fn from(err: ChaincodeStubError) -> Self {
+        ChaincodeError::internal(err.to_string())
+    }
+}
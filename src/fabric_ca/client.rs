@@ -1,9 +1,18 @@
+use std::time::{Duration, SystemTime};
+
+use ecdsa::{SigningKey, elliptic_curve::Generate};
+use p256::NistP256;
 use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::{BuilderError, FabricCAError},
-    identity::Identity,
+    fabric_ca::csr,
+    identity::{CertificateInfo, Identity, IdentityBuilder},
+    implement::{
+        certificate::{certificate_der, serial_hex, to_system_time},
+        private_key::PrivateKey,
+    },
 };
 
 // RFC 3986 path-segment reserved set: everything in CONTROLS plus characters that
@@ -15,6 +24,10 @@ const PATH_SEGMENT: &AsciiSet = &CONTROLS
     .add(b'[').add(b']').add(b'\\').add(b'^').add(b'|')
     .add(b'%');
 
+// Query values additionally must not contain `&` or `+`, which would be read as a
+// parameter separator and a space.
+const QUERY_VALUE: &AsciiSet = &PATH_SEGMENT.add(b'&').add(b'+');
+
 fn encode_segment(s: &str) -> String {
     utf8_percent_encode(s, PATH_SEGMENT).to_string()
 }
@@ -57,8 +70,233 @@ pub struct RevokeRequest {
     pub gen_crl: bool,
 }
 
+/// Request body for adding an affiliation, e.g. `org1.department3`.
+pub struct AddAffiliationRequest {
+    pub name: String,
+    /// Creates missing parent affiliations instead of failing.
+    pub force: bool,
+    pub ca_name: Option<String>,
+}
+
+/// Request body for renaming an affiliation.
+pub struct ModifyAffiliationRequest {
+    pub new_name: String,
+    /// Required when identities belong to the affiliation; moves them along.
+    pub force: bool,
+    pub ca_name: Option<String>,
+}
+
+/// Filters for [`FabricCAClient::list_certificates`]. Fields set to `None` don't
+/// filter. Time bounds are RFC 3339 timestamps, e.g. `"2024-01-01T00:00:00Z"`.
+#[derive(Debug, Clone, Default)]
+pub struct CertificatesRequest {
+    /// Only certificates issued to this enrollment ID.
+    pub enrollment_id: Option<String>,
+    /// Certificate serial number as hex.
+    pub serial: Option<String>,
+    /// Authority Key Identifier as hex.
+    pub aki: Option<String>,
+    pub revoked_start: Option<String>,
+    pub revoked_end: Option<String>,
+    pub expired_start: Option<String>,
+    pub expired_end: Option<String>,
+    /// Excludes expired certificates.
+    pub not_expired: bool,
+    /// Excludes revoked certificates.
+    pub not_revoked: bool,
+    pub ca_name: Option<String>,
+}
+
+/// Request body for generating a CRL. Fields set to `None` don't restrict the
+/// revoked certificates included. Time bounds are RFC 3339 timestamps.
+#[derive(Debug, Clone, Default)]
+pub struct GenCrlRequest {
+    pub ca_name: Option<String>,
+    pub revoked_after: Option<String>,
+    pub revoked_before: Option<String>,
+    pub expire_after: Option<String>,
+    pub expire_before: Option<String>,
+}
+
+/// A subject name entry of an enrollment CSR, like an entry of `csr.names` in a
+/// `fabric-ca-client` configuration. The CA may override these fields.
+#[derive(Debug, Clone, Default)]
+pub struct CsrName {
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub locality: Option<String>,
+    pub organization: Option<String>,
+    pub organizational_unit: Option<String>,
+}
+
+/// An attribute to add to the enrollment certificate. The identity must own it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AttributeRequest {
+    pub name: String,
+    /// When `true`, enrollment succeeds even if the identity lacks the attribute.
+    pub optional: bool,
+}
+
+/// The certificate request of [`EnrollOptions`] and [`ReenrollOptions`].
+#[derive(Debug, Clone, Default)]
+pub struct CsrOptions {
+    /// The common name of the CSR. Defaults to the enrollment ID when
+    /// enrolling and to the one of the current certificate when reenrolling.
+    pub common_name: Option<String>,
+    /// Subject alternative names: DNS names, IP addresses or email addresses.
+    pub hosts: Vec<String>,
+    pub names: Vec<CsrName>,
+    /// The signing profile, e.g. `"tls"` for a TLS certificate.
+    pub profile: Option<String>,
+    /// The label of the HSM key the CA signs with.
+    pub label: Option<String>,
+    /// The CA to enroll with when the server hosts several.
+    pub ca_name: Option<String>,
+    pub attr_reqs: Vec<AttributeRequest>,
+}
+
+impl CsrOptions {
+    fn body<'a>(&'a self, certificate_request: &'a str) -> EnrollBody<'a> {
+        EnrollBody {
+            certificate_request,
+            hosts: &self.hosts,
+            profile: self.profile.as_deref(),
+            label: self.label.as_deref(),
+            ca_name: self.ca_name.as_deref(),
+            attr_reqs: &self.attr_reqs,
+        }
+    }
+}
+
+/// Options for [`FabricCAClient::enroll`].
+///
+/// ```no_run
+/// # use fabric_sdk::fabric_ca::{AttributeRequest, CsrOptions, EnrollOptions};
+/// let options = EnrollOptions {
+///     csr: CsrOptions {
+///         profile: Some("tls".into()),
+///         hosts: vec!["peer0.org1.example.com".into()],
+///         attr_reqs: vec![AttributeRequest { name: "role".into(), optional: true }],
+///         ..Default::default()
+///     },
+///     ..EnrollOptions::new("Org1MSP")
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct EnrollOptions {
+    /// The MSP ID of the enrolled identity. It is not known to the CA.
+    pub msp_id: String,
+    pub csr: CsrOptions,
+}
+
+impl EnrollOptions {
+    pub fn new(msp_id: impl Into<String>) -> Self {
+        Self {
+            msp_id: msp_id.into(),
+            csr: CsrOptions::default(),
+        }
+    }
+}
+
+/// Options for [`FabricCAClient::reenroll`].
+#[derive(Debug, Clone, Default)]
+pub struct ReenrollOptions {
+    /// Keeps the current key instead of generating a new P-256 key.
+    pub reuse_key: bool,
+    pub csr: CsrOptions,
+}
+
 // ===== Response types =====
 
+/// A certificate issued by the CA, as returned by [`FabricCAClient::list_certificates`].
+#[derive(Debug, Clone)]
+pub struct IssuedCertificate {
+    pub pem: String,
+    pub info: CertificateInfo,
+}
+
+/// A certificate revocation list, as returned by [`FabricCAClient::generate_crl`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateRevocationList {
+    /// The CRL as returned by the CA, PEM encoded. Add it to the `crls` folder
+    /// of an MSP to make peers and orderers reject the revoked certificates.
+    pub pem: Vec<u8>,
+    pub issuer: String,
+    pub this_update: SystemTime,
+    pub next_update: Option<SystemTime>,
+    pub revoked: Vec<RevokedCertificate>,
+}
+
+/// An entry of a [`CertificateRevocationList`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevokedCertificate {
+    /// The serial number in the format of [`CertificateInfo::serial`].
+    pub serial: String,
+    pub revocation_date: SystemTime,
+    /// The revocation reason as the Fabric CA names it, e.g. `"keycompromise"`.
+    pub reason: Option<String>,
+}
+
+impl CertificateRevocationList {
+    /// Parses a PEM or DER encoded CRL.
+    pub fn parse(crl: impl AsRef<[u8]>) -> Result<Self, FabricCAError> {
+        let invalid = || FabricCAError::ParseError("Invalid CRL".into());
+        let der = certificate_der(crl.as_ref()).ok_or_else(invalid)?;
+        let (_, list) = x509_parser::parse_x509_crl(&der).map_err(|_| invalid())?;
+        Ok(CertificateRevocationList {
+            pem: crl.as_ref().to_vec(),
+            issuer: list.issuer().to_string(),
+            this_update: to_system_time(list.last_update().timestamp()),
+            next_update: list
+                .next_update()
+                .map(|time| to_system_time(time.timestamp())),
+            revoked: list
+                .iter_revoked_certificates()
+                .map(|revoked| RevokedCertificate {
+                    serial: serial_hex(revoked.raw_serial()),
+                    revocation_date: to_system_time(revoked.revocation_date.timestamp()),
+                    reason: revoked
+                        .reason_code()
+                        .and_then(|(_, reason)| revocation_reason(reason.0))
+                        .map(str::to_string),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns whether the certificate with the given serial is revoked.
+    pub fn is_revoked(&self, serial: &str) -> bool {
+        self.revoked.iter().any(|revoked| revoked.serial == serial)
+    }
+}
+
+/// The reason names the Fabric CA accepts in [`RevokeRequest::reason`].
+fn revocation_reason(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0 => "unspecified",
+        1 => "keycompromise",
+        2 => "cacompromise",
+        3 => "affiliationchange",
+        4 => "superseded",
+        5 => "cessationofoperation",
+        6 => "certificatehold",
+        8 => "removefromcrl",
+        9 => "privilegewithdrawn",
+        10 => "aacompromise",
+        _ => return None,
+    })
+}
+
+/// The result of [`FabricCAClient::enroll`] and [`FabricCAClient::reenroll`].
+#[derive(Clone)]
+pub struct Enrollment {
+    /// The enrolled identity with its newly generated private key.
+    pub identity: Identity,
+    /// The PEM encoded certificate chain of the issuing CA.
+    pub ca_chain: Vec<u8>,
+    pub ca_name: String,
+}
+
 /// An identity registered with the Fabric CA.
 #[derive(Debug, Clone, Deserialize)]
 pub struct CAIdentity {
@@ -173,6 +411,76 @@ struct RevokeBody<'a> {
     gen_crl: bool,
 }
 
+#[derive(Serialize)]
+struct AffiliationBody<'a> {
+    name: &'a str,
+    #[serde(rename = "caname", skip_serializing_if = "Option::is_none")]
+    ca_name: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct GenCrlBody<'a> {
+    #[serde(rename = "caname", skip_serializing_if = "Option::is_none")]
+    ca_name: Option<&'a str>,
+    #[serde(rename = "revokedafter", skip_serializing_if = "Option::is_none")]
+    revoked_after: Option<&'a str>,
+    #[serde(rename = "revokedbefore", skip_serializing_if = "Option::is_none")]
+    revoked_before: Option<&'a str>,
+    #[serde(rename = "expireafter", skip_serializing_if = "Option::is_none")]
+    expire_after: Option<&'a str>,
+    #[serde(rename = "expirebefore", skip_serializing_if = "Option::is_none")]
+    expire_before: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct GenCrlResult {
+    #[serde(rename = "CRL")]
+    crl: String,
+}
+
+#[derive(Deserialize)]
+struct CertificatesResult {
+    #[serde(default)]
+    certs: Vec<CertificatePem>,
+}
+
+#[derive(Deserialize)]
+struct CertificatePem {
+    #[serde(rename = "PEM")]
+    pem: String,
+}
+
+#[derive(Serialize)]
+struct EnrollBody<'a> {
+    certificate_request: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    hosts: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    #[serde(rename = "caname", skip_serializing_if = "Option::is_none")]
+    ca_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    attr_reqs: &'a [AttributeRequest],
+}
+
+#[derive(Deserialize)]
+struct EnrollResult {
+    #[serde(rename = "Cert")]
+    cert: String,
+    #[serde(rename = "ServerInfo")]
+    server_info: EnrollServerInfo,
+}
+
+#[derive(Deserialize)]
+struct EnrollServerInfo {
+    #[serde(rename = "CAName", default)]
+    ca_name: String,
+    #[serde(rename = "CAChain", default)]
+    ca_chain: String,
+}
+
 // ===== Builder =====
 
 /// Builder for [`FabricCAClient`].
@@ -244,7 +552,9 @@ impl FabricCAClientBuilder {
         Ok(self)
     }
 
-    /// Sets the admin identity used to authenticate requests.
+    /// Sets the admin identity used to authenticate requests. A client without
+    /// identity can only call [`FabricCAClient::get_ca_info`] and
+    /// [`FabricCAClient::enroll`].
     pub fn with_identity(mut self, identity: Identity) -> Self {
         self.identity = Some(identity);
         self
@@ -275,7 +585,6 @@ impl FabricCAClientBuilder {
 
     pub fn build(self) -> Result<FabricCAClient, BuilderError> {
         let url = self.url.ok_or(BuilderError::MissingParameter("url".into()))?;
-        let identity = self.identity.ok_or(BuilderError::MissingParameter("identity".into()))?;
 
         #[cfg(target_arch = "wasm32")]
         let http_client = reqwest::Client::new();
@@ -296,7 +605,7 @@ impl FabricCAClientBuilder {
 
         Ok(FabricCAClient {
             base_url: url,
-            identity,
+            identity: self.identity,
             http_client,
         })
     }
@@ -324,7 +633,7 @@ fn full_error_chain(e: &dyn std::error::Error) -> String {
 /// Use [`FabricCAClientBuilder`] to construct an instance.
 pub struct FabricCAClient {
     base_url: String,
-    identity: Identity,
+    identity: Option<Identity>,
     http_client: reqwest::Client,
 }
 
@@ -343,6 +652,112 @@ impl FabricCAClient {
         self.unwrap_response(resp)
     }
 
+    /// Enrolls a registered identity with its enrollment secret, the equivalent
+    /// of `fabric-ca-client enroll`. A new P-256 key is generated and the CA
+    /// issues a certificate for it. Does not require authentication.
+    ///
+    /// ```no_run
+    /// # use fabric_sdk::fabric_ca::{EnrollOptions, FabricCAClientBuilder};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ca = FabricCAClientBuilder::new()
+    ///     .with_url("https://ca.org1.example.com:7054")?
+    ///     .build()?;
+    /// let enrollment = ca
+    ///     .enroll("appUser", "appUserPw", EnrollOptions::new("Org1MSP"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn enroll(
+        &self,
+        enrollment_id: impl AsRef<str>,
+        secret: impl AsRef<str>,
+        options: EnrollOptions,
+    ) -> Result<Enrollment, FabricCAError> {
+        let path = "/api/v1/enroll";
+        let enrollment_id = enrollment_id.as_ref();
+        let key = PrivateKey::P256(SigningKey::<NistP256>::generate());
+        let csr = csr::create_csr(
+            &key,
+            options.csr.common_name.as_deref().unwrap_or(enrollment_id),
+            &options.csr.names,
+            &options.csr.hosts,
+        );
+        let body_bytes = serde_json::to_vec(&options.csr.body(&csr))
+            .map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let raw = self
+            .http_client
+            .post(format!("{}{}", self.base_url, path))
+            .basic_auth(enrollment_id, Some(secret.as_ref()))
+            .header("Content-Type", "application/json")
+            .body(body_bytes)
+            .send()
+            .await
+            .map_err(|e| FabricCAError::HttpError(full_error_chain(&e)))?;
+        let parsed = self.parse_response(raw).await?;
+        let result = self.unwrap_response::<EnrollResult>(parsed)?;
+        enrollment_from_result(result, |cert| {
+            IdentityBuilder::from_pem(cert)?
+                .with_msp(options.msp_id)?
+                .with_private_key(key.to_pkcs8_pem()?)?
+                .build()
+        })
+    }
+
+    /// Renews the certificate of `identity`, the equivalent of
+    /// `fabric-ca-client reenroll`. The request is authenticated with
+    /// `identity` itself, so the client doesn't need an identity of its own.
+    /// The returned identity keeps the MSP ID and MSP directory.
+    pub async fn reenroll(
+        &self,
+        identity: &Identity,
+        options: ReenrollOptions,
+    ) -> Result<Enrollment, FabricCAError> {
+        let path = "/api/v1/reenroll";
+        let certificate = identity
+            .get_certificate_info()
+            .map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let new_key =
+            (!options.reuse_key).then(|| PrivateKey::P256(SigningKey::<NistP256>::generate()));
+        let csr = csr::create_csr(
+            new_key.as_ref().unwrap_or(identity.get_private_key()),
+            options
+                .csr
+                .common_name
+                .as_deref()
+                .or(certificate.common_name.as_deref())
+                .unwrap_or_default(),
+            &options.csr.names,
+            &options.csr.hosts,
+        );
+        let body_bytes = serde_json::to_vec(&options.csr.body(&csr))
+            .map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let raw = self
+            .signed_request(identity, reqwest::Method::POST, path, &body_bytes)
+            .await?;
+        let parsed = self.parse_response(raw).await?;
+        let result = self.unwrap_response::<EnrollResult>(parsed)?;
+        enrollment_from_result(result, |cert| identity.renewed(cert, new_key))
+    }
+
+    /// Reenrolls `identity` if its certificate has expired or expires within
+    /// `threshold`, e.g. when called from a periodic task. Returns `None` while
+    /// the certificate is valid for longer.
+    pub async fn reenroll_if_expiring(
+        &self,
+        identity: &Identity,
+        threshold: Duration,
+        options: ReenrollOptions,
+    ) -> Result<Option<Enrollment>, FabricCAError> {
+        let certificate = identity
+            .get_certificate_info()
+            .map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        if !certificate.expires_within(threshold) {
+            return Ok(None);
+        }
+        self.reenroll(identity, options).await.map(Some)
+    }
+
     /// Lists all identities registered with the CA that are visible to the caller's affiliation.
     pub async fn list_identities(&self) -> Result<Vec<CAIdentity>, FabricCAError> {
         let path = "/api/v1/identities";
@@ -434,6 +849,133 @@ impl FabricCAClient {
         self.unwrap_response(resp)
     }
 
+    /// Adds an affiliation and returns it. The caller must hold the
+    /// `hf.AffiliationMgr` attribute.
+    pub async fn add_affiliation(
+        &self,
+        req: AddAffiliationRequest,
+    ) -> Result<Affiliation, FabricCAError> {
+        let path = format!(
+            "/api/v1/affiliations{}",
+            query_string(&[("force", req.force.then_some("true"))])
+        );
+        let body = AffiliationBody {
+            name: &req.name,
+            ca_name: req.ca_name.as_deref(),
+        };
+        let body_bytes =
+            serde_json::to_vec(&body).map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let raw = self
+            .authenticated_request(reqwest::Method::POST, &path, &body_bytes)
+            .await?;
+        let parsed = self.parse_response(raw).await?;
+        self.unwrap_response(parsed)
+    }
+
+    /// Renames the affiliation `name` and returns it with its identities.
+    pub async fn modify_affiliation(
+        &self,
+        name: impl AsRef<str>,
+        req: ModifyAffiliationRequest,
+    ) -> Result<Affiliation, FabricCAError> {
+        let path = format!(
+            "/api/v1/affiliations/{}{}",
+            encode_segment(name.as_ref()),
+            query_string(&[("force", req.force.then_some("true"))])
+        );
+        let body = AffiliationBody {
+            name: &req.new_name,
+            ca_name: req.ca_name.as_deref(),
+        };
+        let body_bytes =
+            serde_json::to_vec(&body).map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let raw = self
+            .authenticated_request(reqwest::Method::PUT, &path, &body_bytes)
+            .await?;
+        let parsed = self.parse_response(raw).await?;
+        self.unwrap_response(parsed)
+    }
+
+    /// Removes an affiliation and returns what was removed. With `force`, sub
+    /// affiliations and identities are removed as well; otherwise the CA
+    /// refuses to remove an affiliation that is still in use.
+    pub async fn remove_affiliation(
+        &self,
+        name: impl AsRef<str>,
+        force: bool,
+    ) -> Result<Affiliation, FabricCAError> {
+        let path = format!(
+            "/api/v1/affiliations/{}{}",
+            encode_segment(name.as_ref()),
+            query_string(&[("force", force.then_some("true"))])
+        );
+        let raw = self
+            .authenticated_request(reqwest::Method::DELETE, &path, b"")
+            .await?;
+        let parsed = self.parse_response(raw).await?;
+        self.unwrap_response(parsed)
+    }
+
+    /// Lists the certificates issued by the CA that match the filters and are
+    /// visible to the caller's affiliation.
+    pub async fn list_certificates(
+        &self,
+        req: CertificatesRequest,
+    ) -> Result<Vec<IssuedCertificate>, FabricCAError> {
+        let path = format!(
+            "/api/v1/certificates{}",
+            query_string(&[
+                ("id", req.enrollment_id.as_deref()),
+                ("serial", req.serial.as_deref()),
+                ("aki", req.aki.as_deref()),
+                ("revoked_start", req.revoked_start.as_deref()),
+                ("revoked_end", req.revoked_end.as_deref()),
+                ("expired_start", req.expired_start.as_deref()),
+                ("expired_end", req.expired_end.as_deref()),
+                ("notexpired", req.not_expired.then_some("true")),
+                ("notrevoked", req.not_revoked.then_some("true")),
+                ("ca", req.ca_name.as_deref()),
+            ])
+        );
+        let resp = self.authenticated_get(&path).await?;
+        self.unwrap_response::<CertificatesResult>(resp)?
+            .certs
+            .into_iter()
+            .map(|cert| {
+                let info = CertificateInfo::parse(&cert.pem)
+                    .map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+                Ok(IssuedCertificate {
+                    pem: cert.pem,
+                    info,
+                })
+            })
+            .collect()
+    }
+
+    /// Generates a CRL of the revoked certificates. The caller must hold the
+    /// `hf.GenCRL` attribute.
+    pub async fn generate_crl(
+        &self,
+        req: GenCrlRequest,
+    ) -> Result<CertificateRevocationList, FabricCAError> {
+        let path = "/api/v1/gencrl";
+        let body = GenCrlBody {
+            ca_name: req.ca_name.as_deref(),
+            revoked_after: req.revoked_after.as_deref(),
+            revoked_before: req.revoked_before.as_deref(),
+            expire_after: req.expire_after.as_deref(),
+            expire_before: req.expire_before.as_deref(),
+        };
+        let body_bytes =
+            serde_json::to_vec(&body).map_err(|e| FabricCAError::ParseError(e.to_string()))?;
+        let raw = self
+            .authenticated_request(reqwest::Method::POST, path, &body_bytes)
+            .await?;
+        let parsed = self.parse_response(raw).await?;
+        let result = self.unwrap_response::<GenCrlResult>(parsed)?;
+        CertificateRevocationList::parse(decode_base64(&result.crl, "CRL")?)
+    }
+
     /// Revokes a certificate by serial + AKI, or all certificates belonging to an identity.
     ///
     /// Set [`RevokeRequest::enrollment_id`] to revoke all certs for that identity, or
@@ -488,9 +1030,18 @@ impl FabricCAClient {
         path: &str,
         body: &[u8],
     ) -> Result<reqwest::Response, FabricCAError> {
-        let token = self
-            .identity
-            .generate_fabric_ca_token(method.as_str(), path, body);
+        let identity = self.identity.as_ref().ok_or(FabricCAError::NotConfigured)?;
+        self.signed_request(identity, method, path, body).await
+    }
+
+    async fn signed_request(
+        &self,
+        identity: &Identity,
+        method: reqwest::Method,
+        path: &str,
+        body: &[u8],
+    ) -> Result<reqwest::Response, FabricCAError> {
+        let token = identity.generate_fabric_ca_token(method.as_str(), path, body);
         self.http_client
             .request(method, format!("{}{}", self.base_url, path))
             .header("Authorization", token)
@@ -542,6 +1093,46 @@ impl FabricCAClient {
     }
 }
 
+/// Builds a query string from the parameters that are set. The Fabric CA
+/// checks the token against the full request URI, so the query is part of the
+/// signed path.
+fn query_string(params: &[(&str, Option<&str>)]) -> String {
+    let query = params
+        .iter()
+        .filter_map(|(name, value)| {
+            value.map(|value| format!("{}={}", name, utf8_percent_encode(value, QUERY_VALUE)))
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    if query.is_empty() {
+        query
+    } else {
+        format!("?{}", query)
+    }
+}
+
+fn decode_base64(value: &str, what: &str) -> Result<Vec<u8>, FabricCAError> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| FabricCAError::ParseError(format!("Invalid {}: {}", what, e)))
+}
+
+fn enrollment_from_result(
+    result: EnrollResult,
+    identity: impl FnOnce(Vec<u8>) -> Result<Identity, BuilderError>,
+) -> Result<Enrollment, FabricCAError> {
+    let cert = decode_base64(&result.cert, "certificate")?;
+    let ca_chain = decode_base64(&result.server_info.ca_chain, "CA chain")?;
+    let identity = identity(cert)
+        .map_err(|e| FabricCAError::ParseError(format!("Invalid enrollment certificate: {}", e)))?;
+    Ok(Enrollment {
+        identity,
+        ca_chain,
+        ca_name: result.server_info.ca_name,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -588,4 +1179,174 @@ mod tests {
         assert_eq!(obj.get("affiliation").and_then(|v| v.as_str()), Some("org1"));
         assert!(obj.contains_key("attrs"));
     }
+
+    #[test]
+    fn enroll_body_uses_fabric_ca_json_tags() {
+        let options = CsrOptions {
+            profile: Some("tls".into()),
+            ca_name: Some("ca-org1".into()),
+            attr_reqs: vec![AttributeRequest {
+                name: "role".into(),
+                optional: true,
+            }],
+            ..Default::default()
+        };
+        let json: serde_json::Value = serde_json::to_value(options.body("csr")).unwrap();
+        let obj = json.as_object().unwrap();
+
+        assert_eq!(obj.get("certificate_request").and_then(|v| v.as_str()), Some("csr"));
+        assert_eq!(obj.get("profile").and_then(|v| v.as_str()), Some("tls"));
+        assert_eq!(obj.get("caname").and_then(|v| v.as_str()), Some("ca-org1"));
+        assert_eq!(json["attr_reqs"][0]["name"], "role");
+        assert_eq!(json["attr_reqs"][0]["optional"], true);
+        assert!(!obj.contains_key("hosts"));
+        assert!(!obj.contains_key("label"));
+    }
+
+    #[test]
+    fn enroll_result_becomes_identity() {
+        use base64::Engine;
+        use ecdsa::elliptic_curve::pkcs8::DecodePrivateKey;
+
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let cert = include_str!("../../tests/resources/pki/user1-cert.pem");
+        let ca_cert = include_str!("../../tests/resources/pki/ca-cert.pem");
+        let response = serde_json::json!({
+            "Cert": b64.encode(cert),
+            "ServerInfo": { "CAName": "ca-org1", "CAChain": b64.encode(ca_cert), "Version": "1.5.13" }
+        });
+        let parse = || serde_json::from_value::<EnrollResult>(response.clone()).unwrap();
+        let key = SigningKey::<NistP256>::from_pkcs8_pem(include_str!(
+            "../../tests/resources/pki/user1-key.pem"
+        ))
+        .unwrap();
+
+        let build = |key: PrivateKey| {
+            move |cert: Vec<u8>| {
+                IdentityBuilder::from_pem(cert)?
+                    .with_msp("Org1MSP")?
+                    .with_private_key(key.to_pkcs8_pem()?)?
+                    .build()
+            }
+        };
+
+        let enrollment = enrollment_from_result(parse(), build(PrivateKey::P256(key))).unwrap();
+        assert_eq!(enrollment.identity.get_msp_id(), "Org1MSP");
+        assert_eq!(enrollment.identity.get_certificate_bytes(), cert.as_bytes());
+        assert_eq!(enrollment.ca_chain, ca_cert.as_bytes());
+        assert_eq!(enrollment.ca_name, "ca-org1");
+
+        let other_key = PrivateKey::P256(SigningKey::<NistP256>::generate());
+        assert!(matches!(
+            enrollment_from_result(parse(), build(other_key.clone())),
+            Err(FabricCAError::ParseError(_))
+        ));
+
+        // A renewed certificate keeps the MSP ID and, unless replaced, the key.
+        let renewed = enrollment_from_result(parse(), |cert| {
+            enrollment.identity.renewed(cert, None)
+        })
+        .unwrap();
+        assert_eq!(renewed.identity.get_msp_id(), "Org1MSP");
+        assert!(matches!(
+            enrollment.identity.renewed(cert.as_bytes().to_vec(), Some(other_key)),
+            Err(BuilderError::KeyMismatch)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reenrolls_only_expiring_certificates() {
+        let identity = IdentityBuilder::from_pem(include_str!(
+            "../../tests/resources/pki/user1-cert.pem"
+        ))
+        .unwrap()
+        .with_msp("Org1MSP")
+        .unwrap()
+        .with_private_key(include_str!("../../tests/resources/pki/user1-key.pem"))
+        .unwrap()
+        .build()
+        .unwrap();
+        // Nothing listens here, so any request would fail.
+        let ca = FabricCAClientBuilder::new()
+            .with_url("http://127.0.0.1:1")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let renewed = ca
+            .reenroll_if_expiring(&identity, Duration::from_secs(60), Default::default())
+            .await
+            .unwrap();
+        assert!(renewed.is_none());
+        assert!(matches!(
+            ca.reenroll_if_expiring(&identity, Duration::MAX, Default::default())
+                .await,
+            Err(FabricCAError::HttpError(_))
+        ));
+        assert!(matches!(
+            ca.list_identities().await,
+            Err(FabricCAError::NotConfigured)
+        ));
+    }
+
+    #[test]
+    fn query_string_skips_unset_parameters() {
+        assert_eq!(query_string(&[("force", None)]), "");
+        assert_eq!(
+            query_string(&[
+                ("id", Some("user 1")),
+                ("serial", None),
+                ("revoked_start", Some("2024-01-01T00:00:00+01:00")),
+                ("notexpired", Some("true")),
+            ]),
+            "?id=user%201&revoked_start=2024-01-01T00%3A00%3A00%2B01%3A00&notexpired=true"
+        );
+    }
+
+    #[test]
+    fn gencrl_body_uses_fabric_ca_json_tags() {
+        let body = GenCrlBody {
+            ca_name: Some("ca-org1"),
+            revoked_after: Some("2024-01-01T00:00:00Z"),
+            revoked_before: None,
+            expire_after: None,
+            expire_before: Some("2030-01-01T00:00:00Z"),
+        };
+        let json: serde_json::Value = serde_json::to_value(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "caname": "ca-org1",
+                "revokedafter": "2024-01-01T00:00:00Z",
+                "expirebefore": "2030-01-01T00:00:00Z",
+            })
+        );
+    }
+
+    #[test]
+    fn parses_generated_crls() {
+        let pem = include_str!("../../tests/resources/pki/ca-crl.pem");
+        let crl = CertificateRevocationList::parse(pem).unwrap();
+        assert_eq!(
+            crl.issuer,
+            "C=US, ST=North Carolina, L=Durham, O=org1.example.com, CN=ca.org1.example.com"
+        );
+        assert_eq!(crl.pem, pem.as_bytes());
+        assert!(crl.next_update.unwrap() > crl.this_update);
+        assert_eq!(
+            crl.revoked,
+            vec![RevokedCertificate {
+                serial: "2002".into(),
+                revocation_date: SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200),
+                reason: Some("keycompromise".into()),
+            }]
+        );
+
+        let user1 =
+            CertificateInfo::parse(include_str!("../../tests/resources/pki/user1-cert.pem"))
+                .unwrap();
+        assert!(crl.is_revoked(&user1.serial));
+        assert!(!crl.is_revoked("1000"));
+        assert!(CertificateRevocationList::parse("no crl").is_err());
+    }
 }
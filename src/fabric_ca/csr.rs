@@ -0,0 +1,260 @@
+//! A minimal DER encoder for the PKCS#10 certificate signing requests sent to
+//! the Fabric CA on enrollment.
+
+use std::net::IpAddr;
+
+use pkcs8::der::pem::{self, LineEnding};
+
+use crate::{fabric_ca::CsrName, implement::private_key::PrivateKey};
+
+const CSR_LABEL: &str = "CERTIFICATE REQUEST";
+
+const ECDSA_WITH_SHA256: &str = "1.2.840.10045.4.3.2";
+const ED25519: &str = "1.3.101.112";
+const EXTENSION_REQUEST: &str = "1.2.840.113549.1.9.14";
+const SUBJECT_ALT_NAME: &str = "2.5.29.17";
+
+const COUNTRY: &str = "2.5.4.6";
+const STATE: &str = "2.5.4.8";
+const LOCALITY: &str = "2.5.4.7";
+const ORGANIZATION: &str = "2.5.4.10";
+const ORGANIZATIONAL_UNIT: &str = "2.5.4.11";
+const COMMON_NAME: &str = "2.5.4.3";
+
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const UTF8_STRING: u8 = 0x0c;
+const PRINTABLE_STRING: u8 = 0x13;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const CONTEXT_0: u8 = 0xa0;
+const SAN_EMAIL: u8 = 0x81;
+const SAN_DNS: u8 = 0x82;
+const SAN_IP: u8 = 0x87;
+
+/// Creates a PEM encoded CSR for `key`. Hosts become subject alternative
+/// names: IP addresses as `iPAddress`, anything containing `@` as
+/// `rfc822Name` and everything else as `dNSName`.
+///
+/// ECDSA keys sign the SHA-256 digest like everywhere else in Fabric, so the
+/// request is `ecdsa-with-SHA256` on P-384 as well.
+pub(crate) fn create_csr(
+    key: &PrivateKey,
+    common_name: &str,
+    names: &[CsrName],
+    hosts: &[String],
+) -> String {
+    let signature_algorithm = match key {
+        PrivateKey::Ed25519(_) => ED25519,
+        PrivateKey::P256(_) | PrivateKey::P384(_) => ECDSA_WITH_SHA256,
+    };
+
+    let mut attributes = Vec::new();
+    if !hosts.is_empty() {
+        attributes = sequence(&[
+            oid(EXTENSION_REQUEST),
+            set(&[sequence(&[sequence(&[
+                oid(SUBJECT_ALT_NAME),
+                tlv(OCTET_STRING, &subject_alt_names(hosts)),
+            ])])]),
+        ]);
+    }
+    let info = sequence(&[
+        tlv(INTEGER, &[0]),
+        subject(common_name, names),
+        key.public_key().to_public_key_der(),
+        tlv(CONTEXT_0, &attributes),
+    ]);
+
+    let signature = key.sign(&info);
+    let mut bit_string = vec![0];
+    bit_string.extend(signature);
+    let request = sequence(&[
+        info,
+        sequence(&[oid(signature_algorithm)]),
+        tlv(BIT_STRING, &bit_string),
+    ]);
+    pem::encode_string(CSR_LABEL, LineEnding::LF, &request)
+        .expect("A DER document is always PEM encodable")
+}
+
+/// The subject in the order the Fabric CA client writes it: C, ST, L, O and
+/// OU of every name, followed by the CN.
+fn subject(common_name: &str, names: &[CsrName]) -> Vec<u8> {
+    let mut rdns = Vec::new();
+    for name in names {
+        let attributes = [
+            (COUNTRY, &name.country),
+            (STATE, &name.state),
+            (LOCALITY, &name.locality),
+            (ORGANIZATION, &name.organization),
+            (ORGANIZATIONAL_UNIT, &name.organizational_unit),
+        ];
+        for (attribute, value) in attributes {
+            if let Some(value) = value {
+                rdns.push(relative_name(attribute, value));
+            }
+        }
+    }
+    rdns.push(relative_name(COMMON_NAME, common_name));
+    sequence(&rdns)
+}
+
+fn relative_name(attribute: &str, value: &str) -> Vec<u8> {
+    let string_type = if attribute == COUNTRY {
+        PRINTABLE_STRING
+    } else {
+        UTF8_STRING
+    };
+    set(&[sequence(&[
+        oid(attribute),
+        tlv(string_type, value.as_bytes()),
+    ])])
+}
+
+fn subject_alt_names(hosts: &[String]) -> Vec<u8> {
+    let names = hosts
+        .iter()
+        .map(|host| match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => tlv(SAN_IP, &ip.octets()),
+            Ok(IpAddr::V6(ip)) => tlv(SAN_IP, &ip.octets()),
+            Err(_) if host.contains('@') => tlv(SAN_EMAIL, host.as_bytes()),
+            Err(_) => tlv(SAN_DNS, host.as_bytes()),
+        })
+        .collect::<Vec<_>>();
+    sequence(&names)
+}
+
+fn sequence(elements: &[Vec<u8>]) -> Vec<u8> {
+    tlv(SEQUENCE, &elements.concat())
+}
+
+fn set(elements: &[Vec<u8>]) -> Vec<u8> {
+    tlv(SET, &elements.concat())
+}
+
+fn oid(dotted: &str) -> Vec<u8> {
+    let arcs = dotted
+        .split('.')
+        .map(|arc| arc.parse::<u64>().expect("Valid OID constant"))
+        .collect::<Vec<_>>();
+    let mut content = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for &arc in &arcs[2..] {
+        let mut bytes = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            bytes.push((rest & 0x7f) as u8 | 0x80);
+            rest >>= 7;
+        }
+        content.extend(bytes.iter().rev());
+    }
+    tlv(OBJECT_IDENTIFIER, &content)
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len_bytes.iter().take_while(|byte| **byte == 0).count();
+        der.push(0x80 | (len_bytes.len() - skip) as u8);
+        der.extend(&len_bytes[skip..]);
+    }
+    der.extend(content);
+    der
+}
+
+#[cfg(test)]
+mod tests {
+    use ecdsa::{SigningKey, elliptic_curve::Generate};
+    use p256::NistP256;
+    use x509_parser::{
+        certification_request::X509CertificationRequest, extensions::GeneralName, prelude::FromDer,
+    };
+
+    use super::*;
+    use crate::implement::{private_key::parse_private_key, public_key::PublicKey};
+
+    #[test]
+    fn creates_signed_requests() {
+        let key = PrivateKey::P256(SigningKey::<NistP256>::generate());
+        let names = [CsrName {
+            country: Some("US".into()),
+            organization: Some("Hyperledger".into()),
+            organizational_unit: Some("Fabric".into()),
+            ..Default::default()
+        }];
+        let hosts = [
+            "peer0.org1.example.com".to_string(),
+            "127.0.0.1".to_string(),
+            "admin@org1.example.com".to_string(),
+        ];
+        let csr = create_csr(&key, "user1", &names, &hosts);
+        let (label, der) = pem::decode_vec(csr.as_bytes()).unwrap();
+        assert_eq!(label, CSR_LABEL);
+
+        let (_, request) = X509CertificationRequest::from_der(&der).unwrap();
+        let info = &request.certification_request_info;
+        assert_eq!(
+            info.subject.to_string(),
+            "C=US, O=Hyperledger, OU=Fabric, CN=user1"
+        );
+
+        let sans = request
+            .requested_extensions()
+            .into_iter()
+            .flatten()
+            .find_map(|extension| match extension {
+                x509_parser::extensions::ParsedExtension::SubjectAlternativeName(san) => {
+                    Some(san.general_names.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(matches!(
+            sans[0],
+            GeneralName::DNSName("peer0.org1.example.com")
+        ));
+        assert!(matches!(sans[1], GeneralName::IPAddress([127, 0, 0, 1])));
+        assert!(matches!(
+            sans[2],
+            GeneralName::RFC822Name("admin@org1.example.com")
+        ));
+
+        let public_key = PublicKey::from_public_key_der(info.subject_pki.raw).unwrap();
+        assert_eq!(public_key, key.public_key());
+        assert!(public_key.verify_x509_signature(
+            &request.signature_algorithm.algorithm.to_id_string(),
+            info.raw,
+            &request.signature_value.data,
+        ));
+    }
+
+    #[test]
+    fn signs_with_existing_keys() {
+        for key in [
+            include_str!("../../tests/resources/pki/user1-p384-key.pem"),
+            include_str!("../../tests/resources/pki/user1-ed25519-key.pem"),
+        ] {
+            let key = parse_private_key(key.as_bytes(), None).unwrap();
+            let csr = create_csr(&key, "user1", &[], &[]);
+            let (_, der) = pem::decode_vec(csr.as_bytes()).unwrap();
+            let (_, request) = X509CertificationRequest::from_der(&der).unwrap();
+            let info = &request.certification_request_info;
+            assert_eq!(info.subject.to_string(), "CN=user1");
+            assert!(request.requested_extensions().is_none());
+
+            let public_key = PublicKey::from_public_key_der(info.subject_pki.raw).unwrap();
+            assert_eq!(public_key, key.public_key());
+            assert!(public_key.verify_x509_signature(
+                &request.signature_algorithm.algorithm.to_id_string(),
+                info.raw,
+                &request.signature_value.data,
+            ));
+        }
+    }
+}
@@ -1,5 +1,9 @@
 mod client;
+mod csr;
 pub use client::{
-    Affiliation, CAAttribute, CAIdentity, CAInfo, FabricCAClient, FabricCAClientBuilder,
-    ModifyIdentityRequest, RegisterIdentityRequest, RevokeRequest,
+    AddAffiliationRequest, Affiliation, AttributeRequest, CAAttribute, CAIdentity, CAInfo,
+    CertificateRevocationList, CertificatesRequest, CsrName, CsrOptions, EnrollOptions,
+    Enrollment, FabricCAClient, FabricCAClientBuilder, GenCrlRequest, IssuedCertificate,
+    ModifyAffiliationRequest, ModifyIdentityRequest, ReenrollOptions, RegisterIdentityRequest,
+    RevokeRequest, RevokedCertificate,
 };
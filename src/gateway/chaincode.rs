@@ -11,6 +11,7 @@ use crate::{
     },
     identity::Identity,
     implement::crypto::{NONCE_LENGTH, generate_nonce, generate_transaction_id},
+    telemetry,
 };
 
 pub struct ChaincodeCallBuilder {
@@ -191,10 +192,24 @@ impl ChaincodeCallBuilder {
             chaincode_id: Some(chaincode_id.clone()),
         };
 
-        self.generate_transaction(
-            chaincode_header_extension.encode_to_vec(),
-            chaincode_proposal_payload.encode_to_vec(),
-        )
+        let span = tracing::debug_span!(
+            "proposal",
+            tx_id = tracing::field::Empty,
+            channel = self.channel_name.as_deref().unwrap_or_default(),
+            chaincode = %chaincode_id.name,
+            function = self.function_name.as_deref().unwrap_or_default(),
+            duration_ms = tracing::field::Empty,
+        );
+        telemetry::observe_sync(span.clone(), "proposal", || {
+            let signed_proposal = self.generate_transaction(
+                chaincode_header_extension.encode_to_vec(),
+                chaincode_proposal_payload.encode_to_vec(),
+            )?;
+            if let Some(header) = telemetry::proposal_channel_header(&signed_proposal) {
+                span.record("tx_id", header.tx_id);
+            }
+            Ok(signed_proposal)
+        })
     }
 
     /// Builds the proposal together with any configured endorsing organizations,
@@ -237,76 +252,6 @@ impl ChaincodeCallBuilder {
         };
         Ok(signed_proposal)
     }
-    ///Generates a message from a chaincode. This is only needed for the chaincode feature.
-    /// This method neither requiers function name or function args
-    /// Payload depends on the message type. For Register it is for example the ID of the chaincode
-    #[cfg(feature = "chaincode")]
-    pub(crate) fn generate_chaincode_message(
-        &self,
-        r#type: crate::fabric::protos::chaincode_message::Type,
-        payload: Vec<u8>,
-    ) -> Result<crate::fabric::protos::ChaincodeMessage, BuilderError> {
-        if self.chaincode_id.is_none() {
-            return Err(BuilderError::MissingParameter("chaincode_id".into()));
-        }
-        let chaincode_id = ChaincodeId {
-            name: self
-                .chaincode_id
-                .as_ref()
-                .expect("Expected value is none.")
-                .clone(),
-            ..Default::default()
-        };
-        let extension = ChaincodeHeaderExtension {
-            chaincode_id: Some(chaincode_id.clone()),
-        };
-        let nonce = match self.nonce {
-            Some(nonce) => nonce,
-            None => generate_nonce(),
-        };
-        let transaction_id = match &self.transaction_id {
-            Some(transaction_id) => transaction_id.clone(),
-            None => generate_transaction_id(
-                &nonce,
-                self.identity
-                    .get_serialized_identity()
-                    .encode_to_vec()
-                    .as_slice(),
-            ),
-        };
-        let header = match &self.header {
-            Some(header) => header.clone(),
-            None => self.generate_header(&nonce, transaction_id.clone(), extension.encode_to_vec()),
-        };
-        let signed_proposal = match &self.proposal {
-            Some(proposal) => proposal.clone(),
-            None => self.generate_proposal(&header, extension.encode_to_vec(), payload),
-        };
-        Ok(crate::fabric::protos::ChaincodeMessage {
-            r#type: r#type.into(),
-            timestamp: Some({
-                #[cfg(feature = "client-wasm")]
-                {
-                    crate::fabric::google_protobuf::Timestamp {
-                        seconds: web_time::SystemTime::now()
-                            .duration_since(web_time::SystemTime::UNIX_EPOCH)
-                            .expect("Invalid duration calc")
-                            .as_secs() as i64,
-                        nanos: 0,
-                    }
-                }
-                #[cfg(not(feature = "client-wasm"))]
-                {
-                    std::time::SystemTime::now().into()
-                }
-            }),
-            payload: chaincode_id.encode_to_vec(),
-            txid: transaction_id,
-            proposal: Some(signed_proposal),
-            chaincode_event: None,
-            channel_id: self.channel_name.clone().unwrap_or_default(),
-        })
-    }
     pub(crate) fn generate_header(
         &self,
         nonce: &[u8],
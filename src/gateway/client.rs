@@ -21,6 +21,7 @@ use crate::{
     },
     identity::Identity,
     implement::crypto::{generate_nonce, generate_transaction_id},
+    telemetry,
 };
 #[cfg(not(feature = "client-wasm"))]
 use crate::gateway::snapshot;
@@ -166,6 +167,28 @@ impl Client {
         &self,
         transaction_id: String,
         channel_id: String,
+    ) -> Result<CommitStatusResponse, SubmitError> {
+        let span = tracing::info_span!(
+            "commit",
+            tx_id = %transaction_id,
+            channel = %channel_id,
+            duration_ms = tracing::field::Empty,
+        );
+        telemetry::observe(
+            span,
+            "commit",
+            self.request_commit_status(transaction_id, channel_id),
+        )
+        .await
+    }
+
+    /// Requests the commit status without opening a span of its own, so
+    /// [wait_for_commit](crate::fabric::common::Envelope::wait_for_commit) can
+    /// label the span with the chaincode of the envelope.
+    pub(crate) async fn request_commit_status(
+        &self,
+        transaction_id: String,
+        channel_id: String,
     ) -> Result<CommitStatusResponse, SubmitError> {
         if self.tonic_connection.channel.is_none() {
             return Err(SubmitError::NotConnected);
@@ -221,6 +244,8 @@ impl Client {
         if self.tonic_connection.channel.is_none() {
             return Err(SubmitError::NotConnected);
         }
+        let header = telemetry::proposal_channel_header(&signed_proposal).unwrap_or_default();
+        let span = telemetry::transaction_span!("evaluate", &header);
 
         let request = EvaluateRequest {
             transaction_id,
@@ -237,25 +262,28 @@ impl Client {
                 .clone(),
         );
 
-        let response = gateway_client.evaluate(request).await;
-        match response {
-            Ok(response) => {
-                let inner = response.into_inner();
-                match inner.result {
-                    Some(result) => {
-                        if result.status != 200 {
-                            Err(SubmitError::NodeError(result.message))
-                        } else {
-                            Ok(result.payload)
+        telemetry::observe(span, "evaluate", async move {
+            let response = gateway_client.evaluate(request).await;
+            match response {
+                Ok(response) => {
+                    let inner = response.into_inner();
+                    match inner.result {
+                        Some(result) => {
+                            if result.status != 200 {
+                                Err(SubmitError::NodeError(result.message))
+                            } else {
+                                Ok(result.payload)
+                            }
                         }
+                        None => Err(SubmitError::NoPayload),
                     }
-                    None => Err(SubmitError::NoPayload),
                 }
+                Err(err) => Err(SubmitError::NodeError(
+                    crate::implement::grpc_error::format_grpc_error(&err),
+                )),
             }
-            Err(err) => Err(SubmitError::NodeError(
-                crate::implement::grpc_error::format_grpc_error(&err),
-            )),
-        }
+        })
+        .await
     }
 
     pub fn get_chaincode_events_request_builder(&self) -> ChaincodeEventsRequestBuilder {
@@ -315,12 +343,17 @@ impl Client {
         )
         .max_encoding_message_size(usize::MAX)
         .max_decoding_message_size(usize::MAX);
-        match endorser_client.process_proposal(signed_proposal).await {
-            Ok(response) => Ok(response.into_inner()),
-            Err(err) => Err(SubmitError::NodeError(
-                crate::implement::grpc_error::format_grpc_error(&err),
-            )),
-        }
+        let header = telemetry::proposal_channel_header(&signed_proposal).unwrap_or_default();
+        let span = telemetry::transaction_span!("process_proposal", &header);
+        telemetry::observe(span, "process_proposal", async move {
+            match endorser_client.process_proposal(signed_proposal).await {
+                Ok(response) => Ok(response.into_inner()),
+                Err(err) => Err(SubmitError::NodeError(
+                    crate::implement::grpc_error::format_grpc_error(&err),
+                )),
+            }
+        })
+        .await
     }
 
     /// Creates a new SnapshotClientWrapper for interacting with the Snapshot service.
@@ -362,6 +395,8 @@ impl Client {
 pub struct ClientBuilder {
     identity: Option<Identity>,
     tls: Option<Vec<u8>>,
+    #[cfg(not(feature = "client-wasm"))]
+    tls_domain_name: Option<String>,
     scheme: Option<String>,
     path: Option<String>,
     authority: Option<String>,
@@ -415,6 +450,16 @@ impl ClientBuilder {
         self.tls = Some(bytes.into());
         Ok(self)
     }
+    /// Name to verify the node's TLS certificate against, if it differs from the host in the authority.
+    /// Corresponds to `ssl-target-name-override` in connection profiles, e.g. `peer0.org1.example.com` when connecting through `localhost:7051`.
+    #[cfg(not(feature = "client-wasm"))]
+    pub fn with_tls_domain_name(
+        mut self,
+        domain_name: impl Into<String>,
+    ) -> Result<ClientBuilder, BuilderError> {
+        self.tls_domain_name = Some(validate_non_empty(domain_name, "tls domain name")?);
+        Ok(self)
+    }
     /// Authority for the grpc connection to the node. Default is `localhost:7051` which corresponds to the test network
     pub fn with_authority(
         mut self,
@@ -445,8 +490,11 @@ impl ClientBuilder {
                 None => return Err(BuilderError::MissingParameter("tls".into())),
             };
             //TODO Allow custom tls config
-            let tls_config = tonic::transport::ClientTlsConfig::new()
+            let mut tls_config = tonic::transport::ClientTlsConfig::new()
                 .ca_certificate(tonic::transport::Certificate::from_pem(tls.as_slice()));
+            if let Some(domain_name) = self.tls_domain_name {
+                tls_config = tls_config.domain_name(domain_name);
+            }
             let scheme = match self.scheme {
                 Some(scheme) => scheme,
                 None => "https".to_string(),
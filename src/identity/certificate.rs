@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use x509_parser::{certificate::X509Certificate, extensions::ParsedExtension};
+
+use crate::{
+    error::BuilderError,
+    fabric::common::msp_role::MspRoleType,
+    implement::certificate::{certificate_der, serial_hex, to_system_time, with_certificate},
+    policy::NodeOuConfig,
+};
+
+/// The extension in which the Fabric CA stores the attributes of an enrolled
+/// identity as JSON, e.g. `{"attrs":{"hf.EnrollmentID":"user1","role":"auditor"}}`.
+pub const FABRIC_CA_ATTRIBUTES_OID: &str = "1.2.3.4.5.6.7.8.1";
+
+/// The contents of an X.509 certificate that matter to Fabric applications.
+///
+/// ```no_run
+/// # fn example(identity: fabric_sdk::identity::Identity) -> Result<(), fabric_sdk::error::BuilderError> {
+/// let certificate = identity.get_certificate_info()?;
+/// if certificate.expires_within(std::time::Duration::from_secs(7 * 24 * 60 * 60)) {
+///     println!("{} expires at {:?}", certificate.subject, certificate.not_after);
+/// }
+/// if certificate.attribute("role") == Some("auditor") {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    /// The subject distinguished name, e.g. `C=US, ST=North Carolina, O=Hyperledger, OU=client, CN=user1`.
+    pub subject: String,
+    /// The issuer distinguished name.
+    pub issuer: String,
+    /// The subject's common name (CN), usually the enrollment ID.
+    pub common_name: Option<String>,
+    /// The organizational units (OU) of the subject, in order.
+    pub organizational_units: Vec<String>,
+    /// The serial number as lowercase hex without leading zeros, the format the
+    /// Fabric CA expects for revocations.
+    pub serial: String,
+    pub authority_key_identifier: Option<Vec<u8>>,
+    pub subject_key_identifier: Option<Vec<u8>>,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    /// The attributes of the Fabric CA attribute extension, including the
+    /// `hf.*` attributes. Empty for certificates not issued by a Fabric CA.
+    pub attributes: HashMap<String, String>,
+}
+
+#[derive(serde::Deserialize)]
+struct FabricCaAttributes {
+    #[serde(default)]
+    attrs: HashMap<String, serde_json::Value>,
+}
+
+impl CertificateInfo {
+    /// Parses a PEM or DER encoded certificate.
+    pub fn parse(cert: impl AsRef<[u8]>) -> Result<Self, BuilderError> {
+        let der = certificate_der(cert.as_ref())
+            .ok_or_else(|| BuilderError::InvalidCertificate("invalid PEM".into()))?;
+        with_certificate(&der, Self::from_x509)
+            .ok_or_else(|| BuilderError::InvalidCertificate("invalid X.509 certificate".into()))?
+    }
+
+    fn from_x509(cert: &X509Certificate) -> Result<Self, BuilderError> {
+        let subject = cert.subject();
+        let mut authority_key_identifier = None;
+        let mut subject_key_identifier = None;
+        let mut attributes = HashMap::new();
+        for extension in cert.extensions() {
+            match extension.parsed_extension() {
+                ParsedExtension::AuthorityKeyIdentifier(aki) => {
+                    authority_key_identifier = aki.key_identifier.as_ref().map(|id| id.0.to_vec());
+                }
+                ParsedExtension::SubjectKeyIdentifier(ski) => {
+                    subject_key_identifier = Some(ski.0.to_vec());
+                }
+                _ if extension.oid.to_id_string() == FABRIC_CA_ATTRIBUTES_OID => {
+                    attributes = parse_attributes(extension.value)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(CertificateInfo {
+            subject: subject.to_string(),
+            issuer: cert.issuer().to_string(),
+            common_name: subject
+                .iter_common_name()
+                .next()
+                .and_then(|cn| cn.as_str().ok())
+                .map(str::to_string),
+            organizational_units: subject
+                .iter_organizational_unit()
+                .filter_map(|ou| ou.as_str().ok().map(str::to_string))
+                .collect(),
+            serial: serial_hex(cert.raw_serial()),
+            authority_key_identifier,
+            subject_key_identifier,
+            not_before: to_system_time(cert.validity().not_before.timestamp()),
+            not_after: to_system_time(cert.validity().not_after.timestamp()),
+            attributes,
+        })
+    }
+
+    /// Returns a Fabric CA attribute, e.g. `hf.EnrollmentID` or a custom
+    /// attribute requested during enrollment.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Returns the NodeOU role the certificate's OUs give it within an MSP with
+    /// the given NodeOU configuration. Returns `None` if NodeOUs are disabled or
+    /// no role OU is present.
+    pub fn node_ou_role(&self, node_ous: &NodeOuConfig) -> Option<MspRoleType> {
+        if !node_ous.enabled {
+            return None;
+        }
+        [
+            (&node_ous.client_ou, MspRoleType::Client),
+            (&node_ous.peer_ou, MspRoleType::Peer),
+            (&node_ous.admin_ou, MspRoleType::Admin),
+            (&node_ous.orderer_ou, MspRoleType::Orderer),
+        ]
+        .into_iter()
+        .find(|(ou, _)| self.organizational_units.contains(ou))
+        .map(|(_, role)| role)
+    }
+
+    /// Returns whether `time` lies within the validity window.
+    pub fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// Returns the time left until the certificate expires, or `None` if it
+    /// already has.
+    pub fn time_until_expiry(&self) -> Option<Duration> {
+        self.not_after.duration_since(now()).ok()
+    }
+
+    /// Returns whether the certificate has expired or will within `duration`,
+    /// e.g. to renew it ahead of time.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        self.time_until_expiry()
+            .is_none_or(|remaining| remaining <= duration)
+    }
+}
+
+fn parse_attributes(value: &[u8]) -> Result<HashMap<String, String>, BuilderError> {
+    let attributes: FabricCaAttributes = serde_json::from_slice(value).map_err(|err| {
+        BuilderError::InvalidCertificate(format!("Invalid Fabric CA attributes: {}", err))
+    })?;
+    Ok(attributes
+        .attrs
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(value) => (name, value),
+            value => (name, value.to_string()),
+        })
+        .collect())
+}
+
+fn now() -> SystemTime {
+    #[cfg(feature = "client-wasm")]
+    {
+        SystemTime::UNIX_EPOCH
+            + web_time::SystemTime::now()
+                .duration_since(web_time::SystemTime::UNIX_EPOCH)
+                .expect("Invalid duration calc")
+    }
+    #[cfg(not(feature = "client-wasm"))]
+    {
+        SystemTime::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_fabric_ca_certificates() {
+        let info = CertificateInfo::parse(include_str!("../../tests/resources/pki/user1-cert.pem"))
+            .unwrap();
+        assert_eq!(
+            info.subject,
+            "C=US, ST=North Carolina, O=Hyperledger, OU=client, OU=org1, OU=department1, CN=user1"
+        );
+        assert_eq!(
+            info.issuer,
+            "C=US, ST=North Carolina, L=Durham, O=org1.example.com, CN=ca.org1.example.com"
+        );
+        assert_eq!(info.common_name.as_deref(), Some("user1"));
+        assert_eq!(info.organizational_units, ["client", "org1", "department1"]);
+        assert_eq!(info.serial, "2002");
+        assert_eq!(
+            hex::encode(info.subject_key_identifier.as_ref().unwrap()),
+            "d7844289275f76b59b0952c41856c931dea36384"
+        );
+        assert_eq!(
+            hex::encode(info.authority_key_identifier.as_ref().unwrap()),
+            "ad0908130b3b39e8c0bef528769d3ddd5c642fca"
+        );
+        assert_eq!(info.attribute("hf.EnrollmentID"), Some("user1"));
+        assert_eq!(info.attribute("role"), Some("auditor"));
+        assert_eq!(info.attribute("missing"), None);
+
+        assert!(info.is_valid_at(info.not_before + Duration::from_secs(1)));
+        assert!(!info.is_valid_at(info.not_before - Duration::from_secs(1)));
+        assert!(!info.expires_within(Duration::from_secs(60)));
+        assert!(info.expires_within(Duration::from_secs(200 * 365 * 24 * 60 * 60)));
+
+        assert_eq!(
+            info.node_ou_role(&NodeOuConfig::default()),
+            Some(MspRoleType::Client)
+        );
+        let disabled = NodeOuConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(info.node_ou_role(&disabled), None);
+    }
+
+    #[test]
+    fn handles_certificates_without_fabric_extensions() {
+        let info =
+            CertificateInfo::parse(include_str!("../../tests/resources/pki/ca-cert.pem")).unwrap();
+        assert_eq!(info.serial, "1000");
+        assert!(info.attributes.is_empty());
+        assert!(info.authority_key_identifier.is_none());
+        assert_eq!(info.node_ou_role(&NodeOuConfig::default()), None);
+
+        assert!(matches!(
+            CertificateInfo::parse("no certificate"),
+            Err(BuilderError::InvalidCertificate(_))
+        ));
+    }
+}
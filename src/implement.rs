@@ -1,16 +1,26 @@
+/// Helpers for reading X.509 certificates
+pub(crate) mod certificate;
 /// This mod contains general functions used across different implementations
 pub mod crypto;
-#[cfg(any(feature = "client", feature = "client-wasm"))]
-pub(crate) mod grpc_error;
 /// Implementation for the Envelope proto struct
 pub mod envelope;
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+pub(crate) mod grpc_error;
 /// Implementation for the Header proto struct
 pub mod header;
 /// Implementation for the Payload proto struct
 pub mod payload;
+/// Private keys of MSP identities and decoding of PKCS#8 and SEC1 documents
+pub(crate) mod private_key;
 /// Implementation for the Proposal proto struct
 pub mod proposal;
+/// Implementation for the ProposalResponse proto struct
+pub mod proposal_response;
+/// Public keys of MSP identities and signature verification
+pub(crate) mod public_key;
 /// Implementation for the SignedProposal proto struct
 pub mod signed_proposal;
 /// Implementation for the Transaction proto struct
 pub mod transaction;
+/// Signature verification and MSP certificate chain validation
+pub mod verify;
@@ -0,0 +1,59 @@
+use std::time::{Duration, SystemTime};
+
+use x509_parser::{certificate::X509Certificate, prelude::FromDer};
+
+/// Returns the DER encoding of a certificate given either as PEM or as DER.
+/// Fabric serializes identities with PEM certificates, but DER shows up in
+/// MSP configs and CA responses.
+pub(crate) fn certificate_der(bytes: &[u8]) -> Option<Vec<u8>> {
+    if bytes.trim_ascii_start().starts_with(b"-----BEGIN") {
+        x509_parser::pem::parse_x509_pem(bytes)
+            .ok()
+            .map(|(_, pem)| pem.contents)
+    } else {
+        Some(bytes.to_vec())
+    }
+}
+
+/// Parses `der` and hands the certificate to `f`. Returns `None` if the bytes
+/// are not a valid X.509 certificate.
+pub(crate) fn with_certificate<T>(der: &[u8], f: impl FnOnce(&X509Certificate) -> T) -> Option<T> {
+    X509Certificate::from_der(der)
+        .ok()
+        .map(|(_, cert)| f(&cert))
+}
+
+/// Returns the organizational units (OU) of the certificate's subject, in
+/// order. Fabric uses these for NodeOU role classification.
+pub(crate) fn organizational_units(cert: &[u8]) -> Vec<String> {
+    certificate_der(cert)
+        .and_then(|der| {
+            with_certificate(&der, |cert| {
+                cert.subject()
+                    .iter_organizational_unit()
+                    .filter_map(|ou| ou.as_str().ok().map(str::to_string))
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Formats a certificate serial as lowercase hex without leading zeros, the
+/// format the Fabric CA uses for revocations.
+pub(crate) fn serial_hex(raw_serial: &[u8]) -> String {
+    let serial = hex::encode(raw_serial);
+    match serial.trim_start_matches('0') {
+        "" => "0".to_string(),
+        serial => serial.to_string(),
+    }
+}
+
+/// Converts a Unix timestamp of an X.509 time field.
+pub(crate) fn to_system_time(timestamp: i64) -> SystemTime {
+    let offset = Duration::from_secs(timestamp.unsigned_abs());
+    if timestamp >= 0 {
+        SystemTime::UNIX_EPOCH + offset
+    } else {
+        SystemTime::UNIX_EPOCH - offset
+    }
+}
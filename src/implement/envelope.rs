@@ -1,12 +1,20 @@
 use prost::{DecodeError, Message};
 
-use crate::fabric::common::{Envelope, Payload};
+use crate::{
+    error::VerifyError,
+    fabric::{
+        common::{Envelope, Payload},
+        msp::SerializedIdentity,
+    },
+    implement::verify::{MspTrustStore, verify_signature},
+};
 
 #[cfg(any(feature = "client", feature = "client-wasm"))]
 use crate::{
     error::SubmitError,
     fabric::gateway::{CommitStatusResponse, SubmitRequest},
     implement::crypto::{generate_nonce, generate_transaction_id},
+    telemetry,
 };
 
 #[cfg(any(feature = "client", feature = "client-wasm"))]
@@ -34,28 +42,33 @@ impl Envelope {
                 .as_slice(),
         );
 
+        let header = self
+            .get_payload()
+            .map_err(|_| SubmitError::DecodeError("Invalid payload"))?
+            .get_header()
+            .expect("Expected header in payload")
+            .get_channel_header()
+            .map_err(|_| SubmitError::DecodeError("Invalid header"))?;
+        let span = telemetry::transaction_span!("submit", &header);
+
         let submit_request = SubmitRequest {
             transaction_id: transaction_id.clone(),
-            channel_id: self
-                .get_payload()
-                .map_err(|_| SubmitError::DecodeError("Invalid payload"))?
-                .get_header()
-                .expect("Expected header in payload")
-                .get_channel_header()
-                .map_err(|_| SubmitError::DecodeError("Invalid header"))?
-                .channel_id
-                .to_string(),
+            channel_id: header.channel_id,
             prepared_transaction: Some(self.clone()),
         };
 
         let mut gateway_client = client.create_gateway();
 
-        match gateway_client.submit(submit_request).await {
-            Ok(_) => Ok(self),
-            Err(err) => Err(SubmitError::NodeError(
-                crate::implement::grpc_error::format_grpc_error(&err),
-            )),
-        }
+        telemetry::observe(span, "submit", async move {
+            match gateway_client.submit(submit_request).await {
+                Ok(_) => Ok(()),
+                Err(err) => Err(SubmitError::NodeError(
+                    crate::implement::grpc_error::format_grpc_error(&err),
+                )),
+            }
+        })
+        .await?;
+        Ok(self)
     }
 
     /// Waits for commit and returns the commit status
@@ -72,7 +85,13 @@ impl Envelope {
             .expect("No header in payload")
             .get_channel_header()
             .map_err(|_| SubmitError::DecodeError("Invalid Channel header in payload header"))?;
-        client.commit_status(header.tx_id, header.channel_id).await
+        let span = telemetry::transaction_span!("commit", &header);
+        telemetry::observe(
+            span,
+            "commit",
+            client.request_commit_status(header.tx_id, header.channel_id),
+        )
+        .await
     }
 }
 
@@ -81,4 +100,37 @@ impl Envelope {
     pub fn get_payload(&self) -> Result<Payload, DecodeError> {
         Payload::decode(self.payload.as_slice())
     }
+
+    /// Verifies that the envelope was signed by the creator in its payload's
+    /// signature header and returns the creator.
+    ///
+    /// This does not check that the creator belongs to its MSP, see
+    /// [verify_creator_with_trust_store](Self::verify_creator_with_trust_store).
+    pub fn verify_creator(&self) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_creator_signature(None)
+    }
+
+    /// Like [verify_creator](Self::verify_creator) but also validates the
+    /// creator's certificate chain against the MSP's CA certificates.
+    pub fn verify_creator_with_trust_store(
+        &self,
+        trust_store: &MspTrustStore,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_creator_signature(Some(trust_store))
+    }
+
+    fn verify_creator_signature(
+        &self,
+        trust_store: Option<&MspTrustStore>,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        let creator = self
+            .get_payload()
+            .map_err(|_| VerifyError::DecodeError("Invalid payload"))?
+            .get_header()
+            .ok_or(VerifyError::DecodeError("No header in payload"))?
+            .get_signature_header()
+            .map_err(|_| VerifyError::DecodeError("Invalid signature header"))?
+            .creator;
+        verify_signature(&creator, &self.payload, &self.signature, trust_store)
+    }
 }
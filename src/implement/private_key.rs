@@ -0,0 +1,178 @@
+use ecdsa::{SigningKey, signature::Signer};
+use p256::NistP256;
+use p384::ecdsa::signature::hazmat::PrehashSigner;
+
+use crate::{
+    error::BuilderError,
+    implement::{crypto::generate_sha256_hash, public_key::PublicKey},
+};
+
+// Password-based decryption is only available in pkcs8 0.10, which is also the
+// version used by the P-384 and Ed25519 crates. P-256 keys are decoded with the
+// pkcs8 0.11 traits re-exported by `ecdsa`.
+use pkcs8::{EncryptedPrivateKeyInfo, PrivateKeyInfo, der::pem};
+
+const PKCS8_LABEL: &str = "PRIVATE KEY";
+const SEC1_LABEL: &str = "EC PRIVATE KEY";
+const ENCRYPTED_PKCS8_LABEL: &str = "ENCRYPTED PRIVATE KEY";
+
+/// A private key document with any encryption removed.
+pub(crate) enum PrivateKeyDer {
+    Pkcs8(Vec<u8>),
+    Sec1(Vec<u8>),
+}
+
+/// Decodes a private key given as PEM or DER. Supported are PKCS#8, SEC1
+/// (`EC PRIVATE KEY`) and password-encrypted PKCS#8 documents.
+pub(crate) fn decode_private_key(
+    key: &[u8],
+    password: Option<&[u8]>,
+) -> Result<PrivateKeyDer, BuilderError> {
+    if !key.trim_ascii_start().starts_with(b"-----BEGIN") {
+        return decode_der(key, password);
+    }
+    let (label, der) = pem::decode_vec(key.trim_ascii())
+        .map_err(|err| BuilderError::InvalidPrivateKey(format!("Invalid PEM: {}", err)))?;
+    match label {
+        PKCS8_LABEL => Ok(PrivateKeyDer::Pkcs8(der)),
+        SEC1_LABEL => Ok(PrivateKeyDer::Sec1(der)),
+        ENCRYPTED_PKCS8_LABEL => decrypt(&der, password),
+        label => Err(BuilderError::InvalidPrivateKey(format!(
+            "Unsupported PEM label {}",
+            label
+        ))),
+    }
+}
+
+/// DER carries no label, so the document type is guessed. Encrypted documents
+/// are recognized by their structure and have to decrypt, everything else is
+/// tried as PKCS#8 and SEC1 when parsing the key.
+fn decode_der(key: &[u8], password: Option<&[u8]>) -> Result<PrivateKeyDer, BuilderError> {
+    if EncryptedPrivateKeyInfo::try_from(key).is_ok() {
+        decrypt(key, password)
+    } else if PrivateKeyInfo::try_from(key).is_ok() {
+        Ok(PrivateKeyDer::Pkcs8(key.to_vec()))
+    } else {
+        Ok(PrivateKeyDer::Sec1(key.to_vec()))
+    }
+}
+
+fn decrypt(der: &[u8], password: Option<&[u8]>) -> Result<PrivateKeyDer, BuilderError> {
+    let password = password.ok_or_else(|| {
+        BuilderError::MissingParameter("password for the encrypted private key".into())
+    })?;
+    let document = EncryptedPrivateKeyInfo::try_from(der)
+        .and_then(|info| info.decrypt(password))
+        .map_err(|_| {
+            BuilderError::InvalidPrivateKey(
+                "Couldn't decrypt private key, the password may be wrong".into(),
+            )
+        })?;
+    Ok(PrivateKeyDer::Pkcs8(document.as_bytes().to_vec()))
+}
+
+/// The private key of an identity. Fabric 3.x supports ECDSA on P-256 and
+/// P-384 as well as Ed25519 MSP identities.
+#[derive(Clone)]
+pub(crate) enum PrivateKey {
+    P256(SigningKey<NistP256>),
+    P384(p384::ecdsa::SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+impl PrivateKey {
+    /// Signs a message the way Fabric's MSP does. ECDSA signs the SHA-256
+    /// digest of the message, also on P-384, and the signature is normalized
+    /// to low-S and DER encoded. Ed25519 signs the message itself.
+    pub(crate) fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            PrivateKey::P256(key) => {
+                let signature: ecdsa::Signature<NistP256> = key.sign(message);
+                signature.normalize_s().to_der().to_bytes().to_vec()
+            }
+            PrivateKey::P384(key) => {
+                let signature: p384::ecdsa::Signature = key
+                    .sign_prehash(&generate_sha256_hash(message))
+                    .expect("A SHA-256 digest is a valid P-384 prehash");
+                signature
+                    .normalize_s()
+                    .unwrap_or(signature)
+                    .to_der()
+                    .as_bytes()
+                    .to_vec()
+            }
+            PrivateKey::Ed25519(key) => ed25519_dalek::Signer::sign(key, message)
+                .to_bytes()
+                .to_vec(),
+        }
+    }
+
+    pub(crate) fn public_key(&self) -> PublicKey {
+        match self {
+            PrivateKey::P256(key) => PublicKey::P256(*key.verifying_key()),
+            PrivateKey::P384(key) => PublicKey::P384(*key.verifying_key()),
+            PrivateKey::Ed25519(key) => PublicKey::Ed25519(key.verifying_key()),
+        }
+    }
+
+    /// Encodes the key as unencrypted PKCS#8 PEM.
+    #[cfg(any(feature = "client", feature = "client-wasm"))]
+    pub(crate) fn to_pkcs8_pem(&self) -> Result<String, BuilderError> {
+        use pkcs8::EncodePrivateKey;
+
+        let invalid =
+            |err: &dyn std::fmt::Display| BuilderError::InvalidPrivateKey(err.to_string());
+        match self {
+            PrivateKey::P256(key) => {
+                use ecdsa::elliptic_curve::pkcs8::{EncodePrivateKey, LineEnding};
+                key.to_pkcs8_pem(LineEnding::LF)
+                    .map(|pem| pem.to_string())
+                    .map_err(|err| invalid(&err))
+            }
+            PrivateKey::P384(key) => key
+                .to_pkcs8_pem(pkcs8::LineEnding::LF)
+                .map(|pem| pem.to_string())
+                .map_err(|err| invalid(&err)),
+            PrivateKey::Ed25519(key) => key
+                .to_pkcs8_pem(pkcs8::LineEnding::LF)
+                .map(|pem| pem.to_string())
+                .map_err(|err| invalid(&err)),
+        }
+    }
+}
+
+/// Decodes an ECDSA P-256, P-384 or Ed25519 private key.
+pub(crate) fn parse_private_key(
+    key: &[u8],
+    password: Option<&[u8]>,
+) -> Result<PrivateKey, BuilderError> {
+    match decode_private_key(key, password)? {
+        PrivateKeyDer::Pkcs8(der) => {
+            use ecdsa::elliptic_curve::pkcs8::DecodePrivateKey;
+            use pkcs8::DecodePrivateKey as _;
+
+            if let Ok(key) = SigningKey::<NistP256>::from_pkcs8_der(&der) {
+                Ok(PrivateKey::P256(key))
+            } else if let Ok(key) = p384::ecdsa::SigningKey::from_pkcs8_der(&der) {
+                Ok(PrivateKey::P384(key))
+            } else if let Ok(key) = ed25519_dalek::SigningKey::from_pkcs8_der(&der) {
+                Ok(PrivateKey::Ed25519(key))
+            } else {
+                Err(BuilderError::InvalidPrivateKey(
+                    "Unsupported PKCS#8 key, expected ECDSA P-256, P-384 or Ed25519".into(),
+                ))
+            }
+        }
+        PrivateKeyDer::Sec1(der) => {
+            if let Ok(key) = p256::SecretKey::from_sec1_der(&der) {
+                Ok(PrivateKey::P256(key.into()))
+            } else if let Ok(key) = p384::SecretKey::from_sec1_der(&der) {
+                Ok(PrivateKey::P384(key.into()))
+            } else {
+                Err(BuilderError::InvalidPrivateKey(
+                    "Unsupported SEC1 key, expected ECDSA P-256 or P-384".into(),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    error::VerifyError,
+    fabric::{msp::SerializedIdentity, protos::ProposalResponse},
+    implement::verify::{MspTrustStore, verify_signature},
+};
+
+impl ProposalResponse {
+    /// Verifies the endorser's signature over the response payload and returns
+    /// the endorser. Peers sign the payload concatenated with the serialized
+    /// endorser identity.
+    ///
+    /// This does not check that the endorser belongs to its MSP, see
+    /// [verify_endorsement_with_trust_store](Self::verify_endorsement_with_trust_store).
+    pub fn verify_endorsement(&self) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_endorser_signature(None)
+    }
+
+    /// Like [verify_endorsement](Self::verify_endorsement) but also validates
+    /// the endorser's certificate chain against the MSP's CA certificates.
+    pub fn verify_endorsement_with_trust_store(
+        &self,
+        trust_store: &MspTrustStore,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_endorser_signature(Some(trust_store))
+    }
+
+    fn verify_endorser_signature(
+        &self,
+        trust_store: Option<&MspTrustStore>,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        let endorsement = self
+            .endorsement
+            .as_ref()
+            .ok_or(VerifyError::MissingSignature)?;
+        let signed_bytes = [self.payload.as_slice(), endorsement.endorser.as_slice()].concat();
+        verify_signature(
+            &endorsement.endorser,
+            &signed_bytes,
+            &endorsement.signature,
+            trust_store,
+        )
+    }
+}
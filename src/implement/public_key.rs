@@ -0,0 +1,122 @@
+use ecdsa::signature::{Verifier, hazmat::PrehashVerifier};
+use p256::NistP256;
+use p384::ecdsa::signature::hazmat::PrehashVerifier as _;
+use sha2::Digest;
+
+use crate::{error::VerifyError, implement::crypto::generate_sha256_hash};
+
+const ECDSA_WITH_SHA256: &str = "1.2.840.10045.4.3.2";
+const ECDSA_WITH_SHA384: &str = "1.2.840.10045.4.3.3";
+const ECDSA_WITH_SHA512: &str = "1.2.840.10045.4.3.4";
+const ED25519: &str = "1.3.101.112";
+
+/// The public key of an MSP identity.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PublicKey {
+    P256(ecdsa::VerifyingKey<NistP256>),
+    P384(p384::ecdsa::VerifyingKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl PublicKey {
+    /// Decodes a DER `SubjectPublicKeyInfo`, e.g. the one of a certificate.
+    pub(crate) fn from_public_key_der(der: &[u8]) -> Option<Self> {
+        use ecdsa::elliptic_curve::pkcs8::DecodePublicKey;
+        use pkcs8::DecodePublicKey as _;
+
+        if let Ok(key) = ecdsa::VerifyingKey::<NistP256>::from_public_key_der(der) {
+            Some(PublicKey::P256(key))
+        } else if let Ok(key) = p384::ecdsa::VerifyingKey::from_public_key_der(der) {
+            Some(PublicKey::P384(key))
+        } else {
+            ed25519_dalek::VerifyingKey::from_public_key_der(der)
+                .ok()
+                .map(PublicKey::Ed25519)
+        }
+    }
+
+    /// Encodes the key as DER `SubjectPublicKeyInfo`.
+    #[cfg(any(feature = "client", feature = "client-wasm"))]
+    pub(crate) fn to_public_key_der(&self) -> Vec<u8> {
+        use pkcs8::EncodePublicKey;
+
+        let encodable = "A public key is always encodable";
+        match self {
+            PublicKey::P256(key) => {
+                use ecdsa::elliptic_curve::pkcs8::EncodePublicKey;
+                key.to_public_key_der().expect(encodable).into_vec()
+            }
+            PublicKey::P384(key) => key.to_public_key_der().expect(encodable).into_vec(),
+            PublicKey::Ed25519(key) => key.to_public_key_der().expect(encodable).into_vec(),
+        }
+    }
+
+    /// Verifies a signature the way Fabric's MSP does: ECDSA signatures are
+    /// DER encoded over the SHA-256 digest of the message, whatever the curve,
+    /// and must be in low-S form. Ed25519 signs the message itself.
+    pub(crate) fn verify_message(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), VerifyError> {
+        let malformed = || VerifyError::InvalidSignature("malformed signature".into());
+        let mismatch = || VerifyError::InvalidSignature("signature does not match".into());
+        match self {
+            PublicKey::P256(key) => {
+                let signature =
+                    ecdsa::Signature::<NistP256>::from_der(signature).map_err(|_| malformed())?;
+                key.verify(message, &signature).map_err(|_| mismatch())?;
+                if signature.normalize_s() != signature {
+                    return Err(VerifyError::HighS);
+                }
+            }
+            PublicKey::P384(key) => {
+                let signature =
+                    p384::ecdsa::Signature::from_der(signature).map_err(|_| malformed())?;
+                key.verify_prehash(&generate_sha256_hash(message), &signature)
+                    .map_err(|_| mismatch())?;
+                if signature.normalize_s().is_some() {
+                    return Err(VerifyError::HighS);
+                }
+            }
+            PublicKey::Ed25519(key) => {
+                let signature =
+                    ed25519_dalek::Signature::from_slice(signature).map_err(|_| malformed())?;
+                key.verify_strict(message, &signature)
+                    .map_err(|_| mismatch())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the signature of an X.509 certificate or CRL issued by this
+    /// key. `algorithm` is the dotted signature algorithm OID. Unlike message
+    /// signatures, these may be high-S as they come from arbitrary CA tooling.
+    pub(crate) fn verify_x509_signature(
+        &self,
+        algorithm: &str,
+        signed_data: &[u8],
+        signature: &[u8],
+    ) -> bool {
+        let digest = match algorithm {
+            ECDSA_WITH_SHA256 => sha2::Sha256::digest(signed_data).to_vec(),
+            ECDSA_WITH_SHA384 => sha2::Sha384::digest(signed_data).to_vec(),
+            ECDSA_WITH_SHA512 => sha2::Sha512::digest(signed_data).to_vec(),
+            ED25519 => {
+                return match self {
+                    PublicKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                        .is_ok_and(|signature| key.verify_strict(signed_data, &signature).is_ok()),
+                    _ => false,
+                };
+            }
+            _ => return false,
+        };
+        match self {
+            PublicKey::P256(key) => ecdsa::Signature::<NistP256>::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(&digest, &signature).is_ok()),
+            PublicKey::P384(key) => p384::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(&digest, &signature).is_ok()),
+            PublicKey::Ed25519(_) => false,
+        }
+    }
+}
@@ -1,14 +1,55 @@
 use prost::{DecodeError, Message};
 
-use crate::fabric::protos::{Proposal, SignedProposal};
+use crate::{
+    error::VerifyError,
+    fabric::{
+        msp::SerializedIdentity,
+        protos::{Proposal, SignedProposal},
+    },
+    implement::verify::{MspTrustStore, verify_signature},
+};
 
 #[cfg(any(feature = "client", feature = "client-wasm"))]
-use crate::{error::SubmitError, fabric::gateway::EndorseRequest};
+use crate::{error::SubmitError, fabric::gateway::EndorseRequest, telemetry};
 
 impl SignedProposal {
     pub fn get_proposal(&self) -> Result<Proposal, DecodeError> {
         Proposal::decode(self.proposal_bytes.as_slice())
     }
+
+    /// Verifies that the proposal was signed by the creator in its signature
+    /// header and returns the creator. Chaincode can use this on the proposal
+    /// returned by `Context::get_signed_proposal` as well.
+    ///
+    /// This does not check that the creator belongs to its MSP, see
+    /// [verify_with_trust_store](Self::verify_with_trust_store).
+    pub fn verify(&self) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_signature(None)
+    }
+
+    /// Like [verify](Self::verify) but also validates the creator's certificate
+    /// chain against the MSP's CA certificates.
+    pub fn verify_with_trust_store(
+        &self,
+        trust_store: &MspTrustStore,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        self.verify_signature(Some(trust_store))
+    }
+
+    fn verify_signature(
+        &self,
+        trust_store: Option<&MspTrustStore>,
+    ) -> Result<SerializedIdentity, VerifyError> {
+        let creator = self
+            .get_proposal()
+            .map_err(|_| VerifyError::DecodeError("Invalid proposal bytes"))?
+            .get_header()
+            .map_err(|_| VerifyError::DecodeError("Invalid proposal header"))?
+            .get_signature_header()
+            .map_err(|_| VerifyError::DecodeError("Invalid signature header"))?
+            .creator;
+        verify_signature(&creator, &self.proposal_bytes, &self.signature, trust_store)
+    }
 }
 
 #[cfg(any(feature = "client", feature = "client-wasm"))]
@@ -41,22 +82,27 @@ impl SignedProposal {
             .get_channel_header()
             .unwrap();
 
+        let span = telemetry::transaction_span!("endorse", &header);
+
         let endorse_request = EndorseRequest {
             transaction_id: header.tx_id,
             channel_id: header.channel_id,
             proposed_transaction: Some(self),
             endorsing_organizations,
         };
-        //First transaction will be endorsed to the network
-        let response = gateway_client.endorse(endorse_request).await;
-        match response {
-            Ok(response) => match response.into_inner().prepared_transaction {
-                Some(envelope) => Ok(envelope),
-                None => Err(SubmitError::EmptyRespone),
-            },
-            Err(err) => Err(SubmitError::NodeError(
-                crate::implement::grpc_error::format_grpc_error(&err),
-            )),
-        }
+        telemetry::observe(span, "endorse", async move {
+            //First transaction will be endorsed to the network
+            let response = gateway_client.endorse(endorse_request).await;
+            match response {
+                Ok(response) => match response.into_inner().prepared_transaction {
+                    Some(envelope) => Ok(envelope),
+                    None => Err(SubmitError::EmptyRespone),
+                },
+                Err(err) => Err(SubmitError::NodeError(
+                    crate::implement::grpc_error::format_grpc_error(&err),
+                )),
+            }
+        })
+        .await
     }
 }
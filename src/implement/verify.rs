@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use x509_parser::certificate::X509Certificate;
+
+use crate::{
+    error::{BuilderError, VerifyError},
+    fabric::msp::SerializedIdentity,
+    implement::{
+        certificate::{certificate_der, with_certificate},
+        public_key::PublicKey,
+    },
+};
+
+/// Certificate chains longer than this are rejected. Fabric CAs rarely use more
+/// than one intermediate.
+const MAX_CHAIN_LENGTH: usize = 8;
+
+/// The root and intermediate CA certificates of one or more MSPs, used to check
+/// that a signer's certificate was issued by its MSP.
+///
+/// Without a trust store, verification only checks that the signature matches
+/// the certificate embedded in the message, which proves integrity but not that
+/// the signer is a member of the claimed MSP.
+///
+/// ```no_run
+/// use fabric_sdk::implement::verify::MspTrustStore;
+///
+/// # fn example(signed_proposal: fabric_sdk::fabric::protos::SignedProposal) -> Result<(), Box<dyn std::error::Error>> {
+/// let trust_store = MspTrustStore::new()
+///     .with_root_certs("Org1MSP", [std::fs::read("org1/msp/cacerts/ca.org1.example.com-cert.pem")?])?;
+/// let creator = signed_proposal.verify_with_trust_store(&trust_store)?;
+/// println!("signed by {}", creator.mspid);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MspTrustStore {
+    root_certs: HashMap<String, Vec<Vec<u8>>>,
+    intermediate_certs: HashMap<String, Vec<Vec<u8>>>,
+}
+
+impl MspTrustStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds PEM or DER encoded root CA certificates (`cacerts`) of an MSP.
+    pub fn with_root_certs<T, U>(
+        mut self,
+        msp_id: impl Into<String>,
+        certs: T,
+    ) -> Result<Self, BuilderError>
+    where
+        T: IntoIterator<Item = U>,
+        U: AsRef<[u8]>,
+    {
+        let certs = parse_certificates(certs)?;
+        self.root_certs
+            .entry(msp_id.into())
+            .or_default()
+            .extend(certs);
+        Ok(self)
+    }
+
+    /// Adds PEM or DER encoded intermediate CA certificates
+    /// (`intermediatecerts`) of an MSP.
+    pub fn with_intermediate_certs<T, U>(
+        mut self,
+        msp_id: impl Into<String>,
+        certs: T,
+    ) -> Result<Self, BuilderError>
+    where
+        T: IntoIterator<Item = U>,
+        U: AsRef<[u8]>,
+    {
+        let certs = parse_certificates(certs)?;
+        self.intermediate_certs
+            .entry(msp_id.into())
+            .or_default()
+            .extend(certs);
+        Ok(self)
+    }
+
+    /// Adds the CA and intermediate certificates of a loaded MSP directory.
+    pub fn with_msp_directory(
+        self,
+        msp_id: impl Into<String>,
+        msp_directory: &crate::identity::MspDirectory,
+    ) -> Result<Self, BuilderError> {
+        let msp_id = msp_id.into();
+        self.with_root_certs(msp_id.clone(), &msp_directory.ca_certs)?
+            .with_intermediate_certs(msp_id, &msp_directory.intermediate_certs)
+    }
+
+    /// Checks that the DER certificate `der` chains up to a root certificate of
+    /// `msp_id` and that every certificate on the way is currently valid.
+    pub(crate) fn validate(&self, msp_id: &str, der: &[u8]) -> Result<(), VerifyError> {
+        let roots = self.root_certs.get(msp_id).ok_or_else(|| {
+            VerifyError::UntrustedCertificate(format!("no root certificates for MSP {}", msp_id))
+        })?;
+        let intermediates = self
+            .intermediate_certs
+            .get(msp_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut current = der.to_vec();
+        for _ in 0..MAX_CHAIN_LENGTH {
+            check_validity(&current)?;
+            if let Some(root) = roots.iter().find(|root| is_issued_by(&current, root)) {
+                return check_validity(root);
+            }
+            current = intermediates
+                .iter()
+                .find(|intermediate| is_issued_by(&current, intermediate))
+                .ok_or_else(|| {
+                    VerifyError::UntrustedCertificate(format!(
+                        "certificate is not issued by a CA of MSP {}",
+                        msp_id
+                    ))
+                })?
+                .clone();
+        }
+        Err(VerifyError::UntrustedCertificate(
+            "certificate chain is too long".into(),
+        ))
+    }
+}
+
+fn parse_certificates<T, U>(certs: T) -> Result<Vec<Vec<u8>>, BuilderError>
+where
+    T: IntoIterator<Item = U>,
+    U: AsRef<[u8]>,
+{
+    certs
+        .into_iter()
+        .map(|cert| {
+            certificate_der(cert.as_ref())
+                .filter(|der| with_certificate(der, |_| ()).is_some())
+                .ok_or_else(|| BuilderError::InvalidParameter("invalid CA certificate".into()))
+        })
+        .collect()
+}
+
+fn check_validity(der: &[u8]) -> Result<(), VerifyError> {
+    match with_certificate(der, |cert| cert.validity().is_valid()) {
+        Some(true) => Ok(()),
+        Some(false) => Err(VerifyError::UntrustedCertificate(
+            "certificate is expired or not yet valid".into(),
+        )),
+        None => Err(VerifyError::InvalidIdentity("invalid certificate".into())),
+    }
+}
+
+/// Returns whether `issuer` issued and signed `der`.
+fn is_issued_by(der: &[u8], issuer: &[u8]) -> bool {
+    with_certificate(issuer, |issuer| {
+        with_certificate(der, |cert| {
+            cert.issuer() == issuer.subject()
+                && public_key(issuer).is_ok_and(|key| {
+                    key.verify_x509_signature(
+                        &cert.signature_algorithm.algorithm.to_id_string(),
+                        cert.tbs_certificate.as_ref(),
+                        &cert.signature_value.data,
+                    )
+                })
+        })
+        .unwrap_or_default()
+    })
+    .unwrap_or_default()
+}
+
+fn public_key(cert: &X509Certificate) -> Result<PublicKey, VerifyError> {
+    PublicKey::from_public_key_der(cert.public_key().raw).ok_or_else(|| {
+        VerifyError::InvalidIdentity(
+            "certificate does not hold an ECDSA P-256, P-384 or Ed25519 key".into(),
+        )
+    })
+}
+
+/// Verifies that `signature` over `message` was created by the serialized
+/// identity `signer` and, if a trust store is given, that the identity's
+/// certificate was issued by its MSP. Returns the decoded signer.
+pub(crate) fn verify_signature(
+    signer: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    trust_store: Option<&MspTrustStore>,
+) -> Result<SerializedIdentity, VerifyError> {
+    if signer.is_empty() || signature.is_empty() {
+        return Err(VerifyError::MissingSignature);
+    }
+    let identity = SerializedIdentity::decode(signer)
+        .map_err(|_| VerifyError::DecodeError("Invalid serialized identity"))?;
+    let der = certificate_der(&identity.id_bytes)
+        .ok_or_else(|| VerifyError::InvalidIdentity("invalid PEM certificate".into()))?;
+    with_certificate(&der, |cert| {
+        public_key(cert)?.verify_message(message, signature)
+    })
+    .ok_or_else(|| VerifyError::InvalidIdentity("invalid certificate".into()))??;
+    if let Some(trust_store) = trust_store {
+        trust_store.validate(&identity.mspid, &der)?;
+    }
+    Ok(identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityBuilder;
+
+    const CA_CERT: &str = include_str!("../../tests/resources/pki/ca-cert.pem");
+    const USER_CERT: &str = include_str!("../../tests/resources/pki/user1-cert.pem");
+    const USER_KEY: &str = include_str!("../../tests/resources/pki/user1-key.pem");
+
+    fn signer() -> (Vec<u8>, crate::identity::Identity) {
+        let identity = IdentityBuilder::from_pem(USER_CERT)
+            .unwrap()
+            .with_msp("Org1MSP")
+            .unwrap()
+            .with_private_key(USER_KEY)
+            .unwrap()
+            .build()
+            .unwrap();
+        (identity.get_serialized_identity().encode_to_vec(), identity)
+    }
+
+    #[test]
+    fn verifies_signatures_and_rejects_high_s() {
+        let (creator, identity) = signer();
+        let signature = identity.sign_message(b"message");
+
+        let verified = verify_signature(&creator, b"message", &signature, None).unwrap();
+        assert_eq!(verified.mspid, "Org1MSP");
+        assert!(matches!(
+            verify_signature(&creator, b"tampered", &signature, None),
+            Err(VerifyError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            verify_signature(&creator, b"message", &[], None),
+            Err(VerifyError::MissingSignature)
+        ));
+
+        let low_s = ecdsa::Signature::<p256::NistP256>::from_der(&signature).unwrap();
+        let high_s =
+            ecdsa::Signature::<p256::NistP256>::from_scalars(low_s.r(), -*low_s.s()).unwrap();
+        assert!(matches!(
+            verify_signature(&creator, b"message", &high_s.to_der().to_bytes(), None),
+            Err(VerifyError::HighS)
+        ));
+    }
+
+    #[test]
+    fn validates_certificate_chain() {
+        let (creator, identity) = signer();
+        let signature = identity.sign_message(b"message");
+
+        let trust_store = MspTrustStore::new()
+            .with_root_certs("Org1MSP", [CA_CERT])
+            .unwrap();
+        assert!(verify_signature(&creator, b"message", &signature, Some(&trust_store)).is_ok());
+
+        // The CA belongs to another MSP and peer0 did not issue user1's certificate.
+        let trust_store = MspTrustStore::new()
+            .with_root_certs("Org2MSP", [CA_CERT])
+            .unwrap()
+            .with_root_certs(
+                "Org1MSP",
+                [include_str!("../../tests/resources/pki/peer0-cert.pem")],
+            )
+            .unwrap();
+        assert!(matches!(
+            verify_signature(&creator, b"message", &signature, Some(&trust_store)),
+            Err(VerifyError::UntrustedCertificate(_))
+        ));
+
+        assert!(
+            MspTrustStore::new()
+                .with_root_certs("Org1MSP", ["no certificate"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn verifies_protocol_messages() {
+        use crate::fabric::{
+            common::{Envelope, Header, Payload, SignatureHeader},
+            protos::{Endorsement, Proposal, ProposalResponse, SignedProposal},
+        };
+
+        let (creator, identity) = signer();
+        let header = Header {
+            channel_header: vec![],
+            signature_header: SignatureHeader {
+                creator: creator.clone(),
+                nonce: vec![1, 2, 3],
+            }
+            .encode_to_vec(),
+        };
+
+        let proposal_bytes = Proposal {
+            header: header.encode_to_vec(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let mut signed_proposal = SignedProposal {
+            signature: identity.sign_message(&proposal_bytes),
+            proposal_bytes,
+        };
+        assert_eq!(signed_proposal.verify().unwrap().mspid, "Org1MSP");
+        signed_proposal.proposal_bytes.push(0);
+        assert!(signed_proposal.verify().is_err());
+
+        let payload = Payload {
+            header: Some(header),
+            data: b"transaction".to_vec(),
+        }
+        .encode_to_vec();
+        let envelope = Envelope {
+            signature: identity.sign_message(&payload),
+            payload,
+        };
+        assert!(envelope.verify_creator().is_ok());
+
+        let response = ProposalResponse {
+            payload: b"result".to_vec(),
+            endorsement: Some(Endorsement {
+                signature: identity.sign_message(&[b"result".as_slice(), &creator].concat()),
+                endorser: creator,
+            }),
+            ..Default::default()
+        };
+        assert!(response.verify_endorsement().is_ok());
+        assert!(matches!(
+            ProposalResponse::default().verify_endorsement(),
+            Err(VerifyError::MissingSignature)
+        ));
+    }
+
+    #[test]
+    fn verifies_p384_and_ed25519_identities() {
+        for (msp_id, cert, key, ca) in [
+            (
+                "Org2MSP",
+                include_str!("../../tests/resources/pki/user1-p384-cert.pem"),
+                include_str!("../../tests/resources/pki/user1-p384-key.pem"),
+                include_str!("../../tests/resources/pki/ca-p384-cert.pem"),
+            ),
+            (
+                "Org3MSP",
+                include_str!("../../tests/resources/pki/user1-ed25519-cert.pem"),
+                include_str!("../../tests/resources/pki/user1-ed25519-key.pem"),
+                include_str!("../../tests/resources/pki/ca-ed25519-cert.pem"),
+            ),
+        ] {
+            let identity = IdentityBuilder::from_pem(cert)
+                .unwrap()
+                .with_msp(msp_id)
+                .unwrap()
+                .with_private_key(key)
+                .unwrap()
+                .build()
+                .unwrap();
+            let creator = identity.get_serialized_identity().encode_to_vec();
+            let signature = identity.sign_message(b"message");
+
+            let trust_store = MspTrustStore::new().with_root_certs(msp_id, [ca]).unwrap();
+            let verified =
+                verify_signature(&creator, b"message", &signature, Some(&trust_store)).unwrap();
+            assert_eq!(verified.mspid, msp_id);
+            assert!(verify_signature(&creator, b"tampered", &signature, None).is_err());
+
+            let foreign_ca = MspTrustStore::new()
+                .with_root_certs(msp_id, [CA_CERT])
+                .unwrap();
+            assert!(verify_signature(&creator, b"message", &signature, Some(&foreign_ca)).is_err());
+        }
+    }
+}
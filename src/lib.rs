@@ -29,8 +29,18 @@ pub mod error;
 pub mod gateway;
 #[cfg(any(feature = "chaincode", feature = "client", feature = "client-wasm"))]
 pub mod identity;
+#[cfg(any(feature = "chaincode", feature = "client", feature = "client-wasm"))]
+pub mod policy;
 #[cfg(any(feature = "client", feature = "client-wasm"))]
 pub mod fabric_ca;
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+pub mod wallet;
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+pub mod connection_profile;
+#[cfg(all(feature = "blocking", not(feature = "client-wasm")))]
+pub mod blocking;
+#[cfg(any(feature = "chaincode", feature = "client", feature = "client-wasm"))]
+pub(crate) mod telemetry;
 /// Collection of functions for the fabric common structs
 #[cfg(any(feature = "chaincode", feature = "client", feature = "client-wasm"))]
 #[allow(dead_code)]
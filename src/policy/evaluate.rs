@@ -0,0 +1,590 @@
+//! Offline evaluation of signature policies.
+//!
+//! [PolicyEvaluator] answers "would these endorsements satisfy this policy?"
+//! before a transaction is submitted, following the same rules as the peer's
+//! validation system chaincode:
+//!
+//! - every identity can be used to satisfy at most one principal,
+//! - `NOutOf` gates are evaluated greedily from left to right, and
+//! - MSP roles are matched on the certificate's organizational units
+//!   according to the MSP's NodeOU configuration.
+//!
+//! Evaluation is purely structural: it does not verify signatures or validate
+//! certificate chains. Principals restricted by `certifiers_identifier` are
+//! matched on MSP ID and OU only.
+
+use std::collections::HashMap;
+
+use prost::Message;
+
+use crate::{
+    error::PolicyError,
+    fabric::{
+        common::{
+            CombinedPrincipal, ImplicitMetaPolicy, MspIdentityAnonymity, MspPrincipal, MspRole,
+            OrganizationUnit, SignaturePolicy, SignaturePolicyEnvelope, implicit_meta_policy,
+            msp_identity_anonymity, msp_principal, msp_role::MspRoleType, signature_policy,
+        },
+        msp::SerializedIdentity,
+        protos::Endorsement,
+    },
+    implement::certificate::{certificate_der, organizational_units},
+};
+
+/// The NodeOU settings of an MSP, as found under `NodeOUs` in its `config.yaml`.
+///
+/// The default matches the configuration generated by `cryptogen` and the
+/// Fabric CA for the test network: NodeOUs enabled with the `client`, `peer`,
+/// `admin` and `orderer` OU identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeOuConfig {
+    pub enabled: bool,
+    pub client_ou: String,
+    pub peer_ou: String,
+    pub admin_ou: String,
+    pub orderer_ou: String,
+}
+
+impl Default for NodeOuConfig {
+    fn default() -> Self {
+        NodeOuConfig {
+            enabled: true,
+            client_ou: "client".into(),
+            peer_ou: "peer".into(),
+            admin_ou: "admin".into(),
+            orderer_ou: "orderer".into(),
+        }
+    }
+}
+
+/// A principal of the policy that was satisfied during evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalMatch {
+    /// Index of the principal in [SignaturePolicyEnvelope::identities].
+    pub principal_index: usize,
+    /// The satisfied principal.
+    pub principal: MspPrincipal,
+    /// Index of the identity that satisfied the principal, in the order the
+    /// identities were passed to the evaluator.
+    pub identity_index: usize,
+}
+
+/// The outcome of evaluating a [SignaturePolicyEnvelope].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyEvaluation {
+    pub satisfied: bool,
+    /// The principals that contributed to the result. If the policy is not
+    /// satisfied these are the matches of the satisfied sub-gates, which helps
+    /// to tell which endorsements are still missing.
+    pub matched: Vec<PrincipalMatch>,
+}
+
+/// The outcome of evaluating an [ImplicitMetaPolicy].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplicitMetaEvaluation {
+    pub satisfied: bool,
+    /// The number of sub-policies that had to be satisfied.
+    pub required: usize,
+    /// The evaluation of every sub-policy, in the order they were given.
+    pub sub_policies: Vec<PolicyEvaluation>,
+}
+
+/// Evaluates signature policies against a set of identities without
+/// contacting the network.
+///
+/// ```no_run
+/// use fabric_sdk::policy::{self, PolicyEvaluator};
+///
+/// # fn example(endorsements: &[fabric_sdk::fabric::protos::Endorsement]) -> Result<(), fabric_sdk::error::PolicyError> {
+/// let endorsement_policy = policy::from_string("AND('Org1MSP.peer', 'Org2MSP.peer')")?;
+/// let evaluation = PolicyEvaluator::new().evaluate_endorsements(&endorsement_policy, endorsements)?;
+/// if !evaluation.satisfied {
+///     println!("missing endorsements, only matched {:?}", evaluation.matched);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEvaluator {
+    node_ous: HashMap<String, NodeOuConfig>,
+    admin_certs: HashMap<String, Vec<Vec<u8>>>,
+}
+
+impl PolicyEvaluator {
+    /// Creates an evaluator that assumes the [default](NodeOuConfig::default)
+    /// NodeOU configuration for every MSP.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the NodeOU configuration of an MSP.
+    pub fn with_node_ous(mut self, msp_id: impl Into<String>, node_ous: NodeOuConfig) -> Self {
+        self.node_ous.insert(msp_id.into(), node_ous);
+        self
+    }
+
+    /// Sets the explicit admin certificates (`admincerts`) of an MSP. These
+    /// identities satisfy the `admin` role regardless of their OUs, which is
+    /// how admins are recognized by MSPs without NodeOUs.
+    pub fn with_admin_certs<T, U>(mut self, msp_id: impl Into<String>, certs: T) -> Self
+    where
+        T: IntoIterator<Item = U>,
+        U: AsRef<[u8]>,
+    {
+        let certs = certs
+            .into_iter()
+            .filter_map(|cert| certificate_der(cert.as_ref()))
+            .collect();
+        self.admin_certs.insert(msp_id.into(), certs);
+        self
+    }
+
+    /// Takes the NodeOU configuration and admin certificates of an MSP from its
+    /// loaded MSP directory.
+    pub fn with_msp_directory(
+        self,
+        msp_id: impl Into<String>,
+        msp_directory: &crate::identity::MspDirectory,
+    ) -> Self {
+        let msp_id = msp_id.into();
+        self.with_node_ous(msp_id.clone(), msp_directory.node_ous.clone())
+            .with_admin_certs(msp_id, &msp_directory.admin_certs)
+    }
+
+    /// Evaluates `policy` against `identities`, e.g. the endorsers of a
+    /// transaction.
+    pub fn evaluate(
+        &self,
+        policy: &SignaturePolicyEnvelope,
+        identities: &[SerializedIdentity],
+    ) -> Result<PolicyEvaluation, PolicyError> {
+        let rule = policy
+            .rule
+            .as_ref()
+            .ok_or_else(|| PolicyError::InvalidEnvelope("envelope has no rule".into()))?;
+        let principals = policy
+            .identities
+            .iter()
+            .map(decode_principal)
+            .collect::<Result<Vec<_>, _>>()?;
+        let identities = identities
+            .iter()
+            .map(EvaluatedIdentity::new)
+            .collect::<Vec<_>>();
+
+        // Like the peer, a signature set only counts an identity once.
+        let mut used = identities
+            .iter()
+            .enumerate()
+            .map(|(i, identity)| {
+                identities[..i]
+                    .iter()
+                    .any(|previous| previous.serialized == identity.serialized)
+            })
+            .collect::<Vec<_>>();
+        let mut matched = vec![];
+        let satisfied = self.evaluate_rule(
+            rule,
+            policy,
+            &principals,
+            &identities,
+            &mut used,
+            &mut matched,
+        )?;
+        Ok(PolicyEvaluation { satisfied, matched })
+    }
+
+    /// Evaluates `policy` against the endorsers of the given endorsements.
+    /// Endorsers that cannot be decoded never satisfy a principal.
+    pub fn evaluate_endorsements(
+        &self,
+        policy: &SignaturePolicyEnvelope,
+        endorsements: &[Endorsement],
+    ) -> Result<PolicyEvaluation, PolicyError> {
+        let identities = endorsements
+            .iter()
+            .map(|endorsement| {
+                SerializedIdentity::decode(endorsement.endorser.as_slice()).unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        self.evaluate(policy, &identities)
+    }
+
+    /// Evaluates an [ImplicitMetaPolicy] given the already resolved
+    /// sub-policies, e.g. the `Endorsement` policy of every application
+    /// organization for `/Channel/Application/Endorsement`.
+    ///
+    /// As on the peer, `ANY` requires one, `ALL` every and `MAJORITY` more than
+    /// half of the sub-policies; with no sub-policies the policy is satisfied.
+    pub fn evaluate_implicit_meta(
+        &self,
+        policy: &ImplicitMetaPolicy,
+        sub_policies: &[SignaturePolicyEnvelope],
+        identities: &[SerializedIdentity],
+    ) -> Result<ImplicitMetaEvaluation, PolicyError> {
+        let rule = implicit_meta_policy::Rule::try_from(policy.rule).map_err(|_| {
+            PolicyError::InvalidEnvelope(format!("unknown implicit meta rule {}", policy.rule))
+        })?;
+        let required = if sub_policies.is_empty() {
+            0
+        } else {
+            match rule {
+                implicit_meta_policy::Rule::Any => 1,
+                implicit_meta_policy::Rule::All => sub_policies.len(),
+                implicit_meta_policy::Rule::Majority => sub_policies.len() / 2 + 1,
+            }
+        };
+        let sub_policies = sub_policies
+            .iter()
+            .map(|sub_policy| self.evaluate(sub_policy, identities))
+            .collect::<Result<Vec<_>, _>>()?;
+        let satisfied = sub_policies
+            .iter()
+            .filter(|evaluation| evaluation.satisfied)
+            .count()
+            >= required;
+        Ok(ImplicitMetaEvaluation {
+            satisfied,
+            required,
+            sub_policies,
+        })
+    }
+
+    fn evaluate_rule(
+        &self,
+        rule: &SignaturePolicy,
+        policy: &SignaturePolicyEnvelope,
+        principals: &[Principal],
+        identities: &[EvaluatedIdentity],
+        used: &mut [bool],
+        matched: &mut Vec<PrincipalMatch>,
+    ) -> Result<bool, PolicyError> {
+        match &rule.r#type {
+            Some(signature_policy::Type::SignedBy(index)) => {
+                let principal_index = usize::try_from(*index)
+                    .ok()
+                    .filter(|index| *index < principals.len())
+                    .ok_or_else(|| {
+                        PolicyError::InvalidEnvelope(format!(
+                            "SignedBy({index}) references a missing identity; the envelope has {}",
+                            principals.len()
+                        ))
+                    })?;
+                for (identity_index, identity) in identities.iter().enumerate() {
+                    if used[identity_index]
+                        || !self.satisfies(identity, &principals[principal_index])
+                    {
+                        continue;
+                    }
+                    used[identity_index] = true;
+                    matched.push(PrincipalMatch {
+                        principal_index,
+                        principal: policy.identities[principal_index].clone(),
+                        identity_index,
+                    });
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Some(signature_policy::Type::NOutOf(n_out_of)) => {
+                let mut verified = 0;
+                for rule in &n_out_of.rules {
+                    // A failed sub-rule must not consume identities.
+                    let mut rule_used = used.to_vec();
+                    let mut rule_matched = vec![];
+                    if self.evaluate_rule(
+                        rule,
+                        policy,
+                        principals,
+                        identities,
+                        &mut rule_used,
+                        &mut rule_matched,
+                    )? {
+                        verified += 1;
+                        used.copy_from_slice(&rule_used);
+                        matched.append(&mut rule_matched);
+                    }
+                }
+                Ok(verified >= n_out_of.n)
+            }
+            None => Err(PolicyError::InvalidEnvelope(
+                "empty signature policy".into(),
+            )),
+        }
+    }
+
+    fn satisfies(&self, identity: &EvaluatedIdentity, principal: &Principal) -> bool {
+        match principal {
+            Principal::Role(role) => {
+                if identity.serialized.mspid != role.msp_identifier {
+                    return false;
+                }
+                match MspRoleType::try_from(role.role) {
+                    Ok(MspRoleType::Member) => true,
+                    Ok(MspRoleType::Admin) => {
+                        self.has_node_ou(identity, |node_ous| &node_ous.admin_ou)
+                            || self.is_admin_cert(identity)
+                    }
+                    Ok(MspRoleType::Client) => {
+                        self.has_node_ou(identity, |node_ous| &node_ous.client_ou)
+                    }
+                    Ok(MspRoleType::Peer) => {
+                        self.has_node_ou(identity, |node_ous| &node_ous.peer_ou)
+                    }
+                    Ok(MspRoleType::Orderer) => {
+                        self.has_node_ou(identity, |node_ous| &node_ous.orderer_ou)
+                    }
+                    Err(_) => false,
+                }
+            }
+            Principal::OrganizationUnit(unit) => {
+                identity.serialized.mspid == unit.msp_identifier
+                    && identity
+                        .organizational_units
+                        .contains(&unit.organizational_unit_identifier)
+            }
+            Principal::Identity(expected) => {
+                expected.mspid == identity.serialized.mspid
+                    && certificate_der(&expected.id_bytes).is_some_and(|der| {
+                        identity.certificate.as_ref().is_some_and(|own| *own == der)
+                    })
+            }
+            // Only idemix identities are anonymous; this SDK works with X.509.
+            Principal::Anonymity(anonymity) => {
+                anonymity.anonymity_type
+                    == i32::from(msp_identity_anonymity::MspIdentityAnonymityType::Nominal)
+            }
+            Principal::Combined(principals) => principals
+                .iter()
+                .all(|principal| self.satisfies(identity, principal)),
+        }
+    }
+
+    fn has_node_ou(
+        &self,
+        identity: &EvaluatedIdentity,
+        ou: impl FnOnce(&NodeOuConfig) -> &String,
+    ) -> bool {
+        let default = NodeOuConfig::default();
+        let node_ous = self
+            .node_ous
+            .get(&identity.serialized.mspid)
+            .unwrap_or(&default);
+        node_ous.enabled && identity.organizational_units.contains(ou(node_ous))
+    }
+
+    fn is_admin_cert(&self, identity: &EvaluatedIdentity) -> bool {
+        match (
+            self.admin_certs.get(&identity.serialized.mspid),
+            &identity.certificate,
+        ) {
+            (Some(admin_certs), Some(certificate)) => admin_certs.contains(certificate),
+            _ => false,
+        }
+    }
+}
+
+/// An identity with its certificate decoded once up front.
+struct EvaluatedIdentity<'a> {
+    serialized: &'a SerializedIdentity,
+    certificate: Option<Vec<u8>>,
+    organizational_units: Vec<String>,
+}
+
+impl<'a> EvaluatedIdentity<'a> {
+    fn new(serialized: &'a SerializedIdentity) -> Self {
+        EvaluatedIdentity {
+            serialized,
+            certificate: certificate_der(&serialized.id_bytes),
+            organizational_units: organizational_units(&serialized.id_bytes),
+        }
+    }
+}
+
+/// A decoded [MspPrincipal].
+enum Principal {
+    Role(MspRole),
+    OrganizationUnit(OrganizationUnit),
+    Identity(SerializedIdentity),
+    Anonymity(MspIdentityAnonymity),
+    Combined(Vec<Principal>),
+}
+
+fn decode_principal(principal: &MspPrincipal) -> Result<Principal, PolicyError> {
+    let invalid =
+        |e: prost::DecodeError| PolicyError::InvalidEnvelope(format!("invalid principal: {e}"));
+    let bytes = principal.principal.as_slice();
+    match msp_principal::Classification::try_from(principal.principal_classification) {
+        Ok(msp_principal::Classification::Role) => {
+            MspRole::decode(bytes).map(Principal::Role).map_err(invalid)
+        }
+        Ok(msp_principal::Classification::OrganizationUnit) => OrganizationUnit::decode(bytes)
+            .map(Principal::OrganizationUnit)
+            .map_err(invalid),
+        Ok(msp_principal::Classification::Identity) => SerializedIdentity::decode(bytes)
+            .map(Principal::Identity)
+            .map_err(invalid),
+        Ok(msp_principal::Classification::Anonymity) => MspIdentityAnonymity::decode(bytes)
+            .map(Principal::Anonymity)
+            .map_err(invalid),
+        Ok(msp_principal::Classification::Combined) => CombinedPrincipal::decode(bytes)
+            .map_err(invalid)?
+            .principals
+            .iter()
+            .map(decode_principal)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Principal::Combined),
+        Err(_) => Err(PolicyError::InvalidEnvelope(format!(
+            "unknown principal classification {}",
+            principal.principal_classification
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::from_string;
+
+    const ADMIN_CERT: &str = include_str!("../../tests/resources/pki/admin-cert.pem");
+    const USER_CERT: &str = include_str!("../../tests/resources/pki/user1-cert.pem");
+    const PEER_CERT: &str = include_str!("../../tests/resources/pki/peer0-cert.pem");
+
+    fn identity(msp_id: &str, cert: &str) -> SerializedIdentity {
+        SerializedIdentity {
+            mspid: msp_id.into(),
+            id_bytes: cert.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn matches_roles_on_node_ous() {
+        let evaluator = PolicyEvaluator::new();
+        let peer = [identity("Org1MSP", PEER_CERT)];
+        let client = [identity("Org1MSP", USER_CERT)];
+        let admin = [identity("Org1MSP", ADMIN_CERT)];
+
+        for (policy, identities, expected) in [
+            ("OR('Org1MSP.peer')", &peer, true),
+            ("OR('Org1MSP.member')", &peer, true),
+            ("OR('Org1MSP.client')", &peer, false),
+            ("OR('Org1MSP.client')", &client, true),
+            ("OR('Org1MSP.admin')", &client, false),
+            ("OR('Org1MSP.admin')", &admin, true),
+            ("OR('Org2MSP.member')", &admin, false),
+        ] {
+            let evaluation = evaluator
+                .evaluate(&from_string(policy).unwrap(), identities)
+                .unwrap();
+            assert_eq!(evaluation.satisfied, expected, "{policy}");
+        }
+
+        // Without NodeOUs, OUs carry no role and admins come from admincerts.
+        let evaluator = PolicyEvaluator::new()
+            .with_node_ous(
+                "Org1MSP",
+                NodeOuConfig {
+                    enabled: false,
+                    ..Default::default()
+                },
+            )
+            .with_admin_certs("Org1MSP", [USER_CERT]);
+        let admin_policy = from_string("OR('Org1MSP.admin')").unwrap();
+        assert!(!evaluator.evaluate(&admin_policy, &admin).unwrap().satisfied);
+        assert!(
+            evaluator
+                .evaluate(&admin_policy, &client)
+                .unwrap()
+                .satisfied
+        );
+    }
+
+    #[test]
+    fn uses_every_identity_at_most_once() {
+        let evaluator = PolicyEvaluator::new();
+        let policy = from_string("AND('Org1MSP.member', 'Org1MSP.member')").unwrap();
+
+        let same_twice = [
+            identity("Org1MSP", PEER_CERT),
+            identity("Org1MSP", PEER_CERT),
+        ];
+        assert!(!evaluator.evaluate(&policy, &same_twice).unwrap().satisfied);
+
+        let two = [
+            identity("Org1MSP", PEER_CERT),
+            identity("Org1MSP", USER_CERT),
+        ];
+        let evaluation = evaluator.evaluate(&policy, &two).unwrap();
+        assert!(evaluation.satisfied);
+        let used = evaluation
+            .matched
+            .iter()
+            .map(|m| (m.principal_index, m.identity_index))
+            .collect::<Vec<_>>();
+        assert_eq!(used, vec![(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn reports_partial_matches_and_rolls_back_failed_gates() {
+        let evaluator = PolicyEvaluator::new();
+        // The first gate can't be satisfied; it must not consume the peer
+        // identity that the second gate needs.
+        let policy =
+            from_string("OutOf(1, AND('Org1MSP.peer', 'Org2MSP.peer'), 'Org1MSP.peer')").unwrap();
+        let evaluation = evaluator
+            .evaluate(&policy, &[identity("Org1MSP", PEER_CERT)])
+            .unwrap();
+        assert!(evaluation.satisfied);
+        assert_eq!(evaluation.matched.len(), 1);
+        assert_eq!(evaluation.matched[0].principal_index, 2);
+
+        let policy = from_string("AND('Org1MSP.peer', 'Org2MSP.peer')").unwrap();
+        let evaluation = evaluator
+            .evaluate(&policy, &[identity("Org1MSP", PEER_CERT)])
+            .unwrap();
+        assert!(!evaluation.satisfied);
+        assert_eq!(evaluation.matched.len(), 1);
+        assert_eq!(evaluation.matched[0].principal_index, 0);
+    }
+
+    #[test]
+    fn evaluates_implicit_meta_policies() {
+        let evaluator = PolicyEvaluator::new();
+        let sub_policies = [
+            from_string("OR('Org1MSP.peer')").unwrap(),
+            from_string("OR('Org2MSP.peer')").unwrap(),
+            from_string("OR('Org3MSP.peer')").unwrap(),
+        ];
+        let identities = [
+            identity("Org1MSP", PEER_CERT),
+            identity("Org2MSP", PEER_CERT),
+        ];
+
+        for (rule, expected) in [
+            (implicit_meta_policy::Rule::Any, true),
+            (implicit_meta_policy::Rule::Majority, true),
+            (implicit_meta_policy::Rule::All, false),
+        ] {
+            let policy = ImplicitMetaPolicy {
+                sub_policy: "Endorsement".into(),
+                rule: rule.into(),
+            };
+            let evaluation = evaluator
+                .evaluate_implicit_meta(&policy, &sub_policies, &identities)
+                .unwrap();
+            assert_eq!(evaluation.satisfied, expected, "{rule:?}");
+            assert_eq!(evaluation.sub_policies.len(), 3);
+        }
+
+        let policy = ImplicitMetaPolicy {
+            sub_policy: "Endorsement".into(),
+            rule: implicit_meta_policy::Rule::All.into(),
+        };
+        assert!(
+            evaluator
+                .evaluate_implicit_meta(&policy, &[], &[])
+                .unwrap()
+                .satisfied
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Signature policies.
+//!
+//! Endorsement policies, collection member policies and key-level (state-based)
+//! endorsement policies are all expressed as [SignaturePolicyEnvelope]s.
+//! Assembling these trees by hand is verbose, so the [dsl] module converts
+//! between envelopes and Fabric's policy language, e.g.
+//! `AND('Org1MSP.member', OutOf(2, 'Org2MSP.peer', 'Org3MSP.peer'))`.
+//!
+//! [PolicyEvaluator] checks offline whether a set of identities, e.g. the
+//! endorsers of a proposal response, satisfies such a policy.
+//!
+//! [KeyEndorsementPolicy] edits the organizations of a key-level endorsement
+//! policy, e.g. from chaincode before setting a key's validation parameter.
+//!
+//! ```no_run
+//! use fabric_sdk::policy;
+//!
+//! let envelope = policy::from_string("OR('Org1MSP.member', 'Org2MSP.member')")?;
+//! assert_eq!(
+//!     policy::to_string(&envelope)?,
+//!     "OR('Org1MSP.member', 'Org2MSP.member')"
+//! );
+//! # Ok::<(), fabric_sdk::error::PolicyError>(())
+//! ```
+//!
+//! [SignaturePolicyEnvelope]: crate::fabric::common::SignaturePolicyEnvelope
+
+pub mod dsl;
+pub mod evaluate;
+pub mod statebased;
+
+pub use dsl::{
+    application_policy_from_string, application_policy_to_string, from_string, to_string,
+};
+pub use evaluate::{
+    ImplicitMetaEvaluation, NodeOuConfig, PolicyEvaluation, PolicyEvaluator, PrincipalMatch,
+};
+pub use statebased::KeyEndorsementPolicy;
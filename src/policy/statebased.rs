@@ -0,0 +1,162 @@
+//! Key-level (state-based) endorsement policies.
+//!
+//! A key's `VALIDATION_PARAMETER` metadata overrides the chaincode endorsement
+//! policy for writes to that key. [KeyEndorsementPolicy] edits the set of
+//! organizations that must endorse such writes, like the `statebased` package
+//! of the Go chaincode library.
+
+use std::collections::BTreeMap;
+
+use prost::Message;
+
+use crate::{
+    error::PolicyError,
+    fabric::common::{
+        MspPrincipal, MspRole, SignaturePolicy, SignaturePolicyEnvelope, msp_principal,
+        msp_role::MspRoleType, signature_policy,
+    },
+    policy::dsl::{n_out_of, role_principal},
+};
+
+/// The organizations whose endorsement is required to change a key. All of
+/// them have to endorse, each with a principal of the given role.
+///
+/// ```no_run
+/// # use fabric_sdk::{fabric::common::msp_role::MspRoleType, policy::KeyEndorsementPolicy};
+/// # fn example(current: Vec<u8>) -> Result<Vec<u8>, fabric_sdk::error::PolicyError> {
+/// let mut policy = KeyEndorsementPolicy::from_bytes(&current)?;
+/// policy.add_orgs(MspRoleType::Peer, &["Org2MSP"]);
+/// policy.del_orgs(&["Org3MSP"]);
+/// Ok(policy.to_bytes())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyEndorsementPolicy {
+    orgs: BTreeMap<String, MspRoleType>,
+}
+
+impl KeyEndorsementPolicy {
+    /// Creates a policy without organizations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the policy of a key from its `VALIDATION_PARAMETER` bytes, a
+    /// marshalled [SignaturePolicyEnvelope]. Empty bytes, as returned for keys
+    /// without a key-level policy, give an empty policy.
+    ///
+    /// Only the role principals of the envelope are kept, as in the Go
+    /// library; the envelope's rule is replaced by [to_envelope](Self::to_envelope).
+    pub fn from_bytes(policy: &[u8]) -> Result<Self, PolicyError> {
+        let envelope = SignaturePolicyEnvelope::decode(policy)
+            .map_err(|err| PolicyError::InvalidEnvelope(err.to_string()))?;
+        Self::from_envelope(&envelope)
+    }
+
+    /// Loads the role principals of an envelope. See [from_bytes](Self::from_bytes).
+    pub fn from_envelope(envelope: &SignaturePolicyEnvelope) -> Result<Self, PolicyError> {
+        let mut orgs = BTreeMap::new();
+        for principal in &envelope.identities {
+            if principal.principal_classification != msp_principal::Classification::Role as i32 {
+                continue;
+            }
+            let role = MspRole::decode(principal.principal.as_slice())
+                .map_err(|err| PolicyError::InvalidEnvelope(format!("invalid MSP role: {err}")))?;
+            let role_type = MspRoleType::try_from(role.role).map_err(|_| {
+                PolicyError::InvalidEnvelope(format!("unknown MSP role type {}", role.role))
+            })?;
+            orgs.insert(role.msp_identifier, role_type);
+        }
+        Ok(KeyEndorsementPolicy { orgs })
+    }
+
+    /// Requires endorsements of `msp_ids` with the given role, e.g.
+    /// [MspRoleType::Peer]. Replaces the role of organizations already in the
+    /// policy.
+    pub fn add_orgs<S: AsRef<str>>(&mut self, role: MspRoleType, msp_ids: &[S]) {
+        for msp_id in msp_ids {
+            self.orgs.insert(msp_id.as_ref().to_string(), role);
+        }
+    }
+
+    /// Removes `msp_ids` from the policy. Unknown organizations are ignored.
+    pub fn del_orgs<S: AsRef<str>>(&mut self, msp_ids: &[S]) {
+        for msp_id in msp_ids {
+            self.orgs.remove(msp_id.as_ref());
+        }
+    }
+
+    /// Returns the MSP IDs of the organizations in the policy, sorted.
+    pub fn list_orgs(&self) -> Vec<String> {
+        self.orgs.keys().cloned().collect()
+    }
+
+    /// Builds the envelope requiring a signature of every organization, with
+    /// the principals sorted by MSP ID like the Go library.
+    pub fn to_envelope(&self) -> SignaturePolicyEnvelope {
+        let identities: Vec<MspPrincipal> = self
+            .orgs
+            .iter()
+            .map(|(msp_id, role)| role_principal(msp_id, *role))
+            .collect();
+        let rules = (0..identities.len() as i32)
+            .map(|index| SignaturePolicy {
+                r#type: Some(signature_policy::Type::SignedBy(index)),
+            })
+            .collect();
+        SignaturePolicyEnvelope {
+            version: 0,
+            rule: Some(n_out_of(identities.len() as i32, rules)),
+            identities,
+        }
+    }
+
+    /// Returns the marshalled envelope, the value of the key's
+    /// `VALIDATION_PARAMETER`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_envelope().encode_to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy;
+
+    #[test]
+    fn edits_the_required_organizations() {
+        let mut key_policy = KeyEndorsementPolicy::new();
+        key_policy.add_orgs(MspRoleType::Peer, &["Org2MSP", "Org1MSP"]);
+        key_policy.add_orgs(MspRoleType::Member, &["Org3MSP"]);
+        assert_eq!(key_policy.list_orgs(), ["Org1MSP", "Org2MSP", "Org3MSP"]);
+        assert_eq!(
+            policy::to_string(&key_policy.to_envelope()).unwrap(),
+            "AND('Org1MSP.peer', 'Org2MSP.peer', 'Org3MSP.member')"
+        );
+
+        let mut loaded = KeyEndorsementPolicy::from_bytes(&key_policy.to_bytes()).unwrap();
+        assert_eq!(loaded, key_policy);
+        loaded.del_orgs(&["Org2MSP", "Unknown"]);
+        assert_eq!(loaded.list_orgs(), ["Org1MSP", "Org3MSP"]);
+        // Policies set by other tools keep their principals.
+        let parsed = policy::from_string("OR('Org1MSP.member', 'Org2MSP.admin')").unwrap();
+        assert_eq!(
+            KeyEndorsementPolicy::from_bytes(&parsed.encode_to_vec())
+                .unwrap()
+                .list_orgs(),
+            ["Org1MSP", "Org2MSP"]
+        );
+    }
+
+    #[test]
+    fn loads_empty_and_rejects_invalid_parameters() {
+        assert_eq!(
+            KeyEndorsementPolicy::from_bytes(&[]).unwrap(),
+            KeyEndorsementPolicy::new()
+        );
+        assert!(matches!(
+            KeyEndorsementPolicy::from_bytes(&[0xff]),
+            Err(PolicyError::InvalidEnvelope(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Shared plumbing for the `tracing` spans emitted by the client and the
+//! chaincode runtime, and for the call metrics of the `metrics` feature.
+//!
+//! Spans use the module path of the call site as target, so the levels can be
+//! tuned per area with the subscriber's filter, e.g.
+//! `fabric_sdk::gateway=info,fabric_sdk::chaincode=debug`.
+
+use std::{fmt::Display, future::Future, time::Duration};
+
+use tracing::{Instrument, Span};
+
+#[cfg(feature = "client-wasm")]
+use web_time::Instant;
+
+#[cfg(not(feature = "client-wasm"))]
+use std::time::Instant;
+
+use crate::fabric::{common::ChannelHeader, protos::SignedProposal};
+
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+use crate::fabric::protos::ChaincodeHeaderExtension;
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+use prost::Message;
+
+/// Creates a span for a transaction step. The span carries the transaction
+/// id, channel and chaincode name of the given channel header and an empty
+/// `duration_ms` field that [observe] fills in.
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+macro_rules! transaction_span {
+    ($name:literal, $channel_header:expr) => {{
+        let channel_header: &$crate::fabric::common::ChannelHeader = $channel_header;
+        tracing::info_span!(
+            $name,
+            tx_id = %channel_header.tx_id,
+            channel = %channel_header.channel_id,
+            chaincode = %$crate::telemetry::chaincode_name(channel_header),
+            duration_ms = tracing::field::Empty,
+        )
+    }};
+}
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+pub(crate) use transaction_span;
+
+/// Decodes the channel header of a signed proposal. Only used to label spans,
+/// so a malformed proposal yields `None` instead of an error.
+pub(crate) fn proposal_channel_header(signed_proposal: &SignedProposal) -> Option<ChannelHeader> {
+    signed_proposal
+        .get_proposal()
+        .ok()?
+        .get_header()
+        .ok()?
+        .get_channel_header()
+        .ok()
+}
+
+/// Returns the name of the chaincode a channel header is addressed to, or an
+/// empty string for headers without a chaincode extension.
+#[cfg(any(feature = "client", feature = "client-wasm"))]
+pub(crate) fn chaincode_name(channel_header: &ChannelHeader) -> String {
+    ChaincodeHeaderExtension::decode(channel_header.extension.as_slice())
+        .ok()
+        .and_then(|extension| extension.chaincode_id)
+        .map(|chaincode_id| chaincode_id.name)
+        .unwrap_or_default()
+}
+
+/// Runs `future` inside `span`, records its duration in the span's
+/// `duration_ms` field and emits a completion or failure event. With the
+/// `metrics` feature the call is counted under `operation` as well.
+pub(crate) async fn observe<T, E: Display>(
+    span: Span,
+    operation: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = future.instrument(span.clone()).await;
+    finish(&span, operation, start.elapsed(), &result);
+    result
+}
+
+/// Synchronous counterpart of [observe].
+pub(crate) fn observe_sync<T, E: Display>(
+    span: Span,
+    operation: &'static str,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = span.in_scope(f);
+    finish(&span, operation, start.elapsed(), &result);
+    result
+}
+
+fn finish<T, E: Display>(
+    span: &Span,
+    operation: &'static str,
+    elapsed: Duration,
+    result: &Result<T, E>,
+) {
+    let duration_ms = elapsed.as_secs_f64() * 1000.0;
+    span.record("duration_ms", duration_ms);
+    span.in_scope(|| match result {
+        Ok(_) => tracing::debug!(duration_ms, "{operation} completed"),
+        Err(err) => tracing::warn!(duration_ms, error = %err, "{operation} failed"),
+    });
+    record(operation, elapsed, result.is_err());
+}
+
+#[cfg(feature = "metrics")]
+fn record(operation: &'static str, elapsed: Duration, failed: bool) {
+    metrics::counter!("fabric_sdk_calls_total", "operation" => operation).increment(1);
+    if failed {
+        metrics::counter!("fabric_sdk_errors_total", "operation" => operation).increment(1);
+    }
+    metrics::histogram!("fabric_sdk_call_duration_seconds", "operation" => operation)
+        .record(elapsed.as_secs_f64());
+}
+
+#[cfg(not(feature = "metrics"))]
+fn record(_operation: &'static str, _elapsed: Duration, _failed: bool) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn observe_passes_results_through() {
+        let ok: Result<u8, String> =
+            observe(tracing::info_span!("test"), "test", async { Ok(1) }).await;
+        assert_eq!(ok, Ok(1));
+        let err: Result<u8, String> = observe_sync(tracing::info_span!("test"), "test", || {
+            Err("failed".to_string())
+        });
+        assert_eq!(err, Err("failed".to_string()));
+    }
+
+    #[cfg(any(feature = "client", feature = "client-wasm"))]
+    #[test]
+    fn reads_chaincode_name_from_header() {
+        use crate::fabric::protos::ChaincodeId;
+
+        let header = ChannelHeader {
+            extension: ChaincodeHeaderExtension {
+                chaincode_id: Some(ChaincodeId {
+                    name: "basic".to_string(),
+                    ..Default::default()
+                }),
+            }
+            .encode_to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(chaincode_name(&header), "basic");
+        assert_eq!(chaincode_name(&ChannelHeader::default()), "");
+    }
+}
@@ -1,7 +1,11 @@
 #![cfg(not(feature = "client-wasm"))]
 
 use fabric_sdk::{
-    fabric_ca::{FabricCAClientBuilder, ModifyIdentityRequest, RegisterIdentityRequest},
+    fabric_ca::{
+        AddAffiliationRequest, CertificatesRequest, EnrollOptions, FabricCAClientBuilder,
+        GenCrlRequest, ModifyAffiliationRequest, ModifyIdentityRequest, ReenrollOptions,
+        RegisterIdentityRequest,
+    },
     identity,
 };
 use std::{env, fs};
@@ -33,7 +37,7 @@ pub async fn run() {
             .as_slice(),
     )
     .unwrap()
-    .with_msp(msp_id)
+    .with_msp(msp_id.clone())
     .unwrap()
     .with_private_key(pkey)
     .unwrap()
@@ -90,6 +94,43 @@ pub async fn run() {
     assert!(!secret.is_empty());
     println!("Registered {}: secret={}", test_id, secret);
 
+    let enrollment = ca
+        .enroll(test_id, &secret, EnrollOptions::new(msp_id))
+        .await
+        .unwrap();
+    let certificate = enrollment.identity.get_certificate_info().unwrap();
+    assert_eq!(certificate.common_name.as_deref(), Some(test_id));
+    assert!(!enrollment.ca_chain.is_empty());
+    println!("Enrolled {}: serial={}", test_id, certificate.serial);
+
+    let reenrollment = ca
+        .reenroll(
+            &enrollment.identity,
+            ReenrollOptions {
+                reuse_key: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let renewed = reenrollment.identity.get_certificate_info().unwrap();
+    assert_eq!(renewed.common_name.as_deref(), Some(test_id));
+    assert_ne!(renewed.serial, certificate.serial);
+    println!("Reenrolled {}: serial={}", test_id, renewed.serial);
+
+    let certificates = ca
+        .list_certificates(CertificatesRequest {
+            enrollment_id: Some(test_id.to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(
+        certificates
+            .iter()
+            .any(|issued| issued.info.serial == renewed.serial)
+    );
+
     let modified = ca
         .modify_identity(
             test_id,
@@ -108,6 +149,39 @@ pub async fn run() {
     ca.remove_identity(test_id).await.unwrap();
     println!("Removed {}", test_id);
 
+    // removing the identity revoked its certificates
+    let crl = ca.generate_crl(GenCrlRequest::default()).await.unwrap();
+    assert!(crl.is_revoked(&renewed.serial));
+    println!("CRL lists {} revoked certificates", crl.revoked.len());
+
+    // add, rename, then remove a test affiliation
+    let _ = ca.remove_affiliation("org1.sdktest", true).await;
+    let _ = ca.remove_affiliation("org1.sdktest-renamed", true).await;
+    let added = ca
+        .add_affiliation(AddAffiliationRequest {
+            name: "org1.sdktest".to_string(),
+            force: false,
+            ca_name: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(added.name, "org1.sdktest");
+    let renamed = ca
+        .modify_affiliation(
+            "org1.sdktest",
+            ModifyAffiliationRequest {
+                new_name: "org1.sdktest-renamed".to_string(),
+                force: false,
+                ca_name: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.name, "org1.sdktest-renamed");
+    ca.remove_affiliation("org1.sdktest-renamed", false)
+        .await
+        .unwrap();
+
     // list affiliations
     let affiliations = ca.list_affiliations().await.unwrap();
     println!("Root affiliation: {}", affiliations.name);